@@ -1,14 +1,41 @@
 use clap::*;
-use kani_evaluator::{nom, Evaluator};
-use std::fs::File;
+use kani_evaluator::kani_parser::format::format;
+use kani_evaluator::object::{Array, Object};
+use kani_evaluator::{nom, Capabilities, Evaluator};
+use std::fs;
 use std::io;
 use std::io::prelude::*;
+use std::process;
 
-fn read_file(path: &str) -> io::Result<String> {
-    let mut file = File::open(path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    Ok(contents)
+/// Exit statuses, taken from BSD's sysexits.h so that they stay clear of the
+/// small codes scripts pick for `exit(n)`.
+const PARSE_ERROR: i32 = 65; // EX_DATAERR
+const INPUT_ERROR: i32 = 66; // EX_NOINPUT
+const RUNTIME_ERROR: i32 = 70; // EX_SOFTWARE
+const OUTPUT_ERROR: i32 = 74; // EX_IOERR
+/// Returned by `kani fmt --check` when a file would be reformatted.
+const UNFORMATTED: i32 = 1;
+
+fn read_source(path: &str) -> io::Result<String> {
+    match path {
+        "-" => {
+            let mut contents = String::new();
+            io::stdin().read_to_string(&mut contents)?;
+            Ok(contents)
+        }
+        _ => fs::read_to_string(path),
+    }
+}
+
+fn parse_error(code: &str, e: nom::Err<nom::error::Error<&str>>) -> String {
+    let rest = match e {
+        nom::Err::Error(e) | nom::Err::Failure(e) => e.input,
+        nom::Err::Incomplete(_) => return "parse error: unexpected end of input".to_string(),
+    };
+    let consumed = &code[..code.len() - rest.len()];
+    let line = consumed.matches('\n').count() + 1;
+    let column = consumed.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    format!("parse error at line {}, column {}", line, column)
 }
 
 fn main() {
@@ -16,25 +43,117 @@ fn main() {
         (version: crate_version!())
         (author: crate_authors!())
         (about: crate_description!())
+        (@setting TrailingVarArg)
         (@arg file: -f --file [PATH] +takes_value "Path of the source file")
         (@arg code: -c --code [CODE] +takes_value "Code you want to run inline")
+        (@arg expression: -e --eval [CODE] +takes_value conflicts_with[code]
+            "Code you want to run inline, printing its value")
+        (@arg print_result: -p --("print-result") "Print the value of the program")
+        (@arg script: [SCRIPT] "Path of the source file, or - to read it from stdin")
+        (@arg args: [ARGS]... "Arguments passed to the script as the `args` array")
+        (@subcommand fmt =>
+            (about: "Formats kani source files in place")
+            (@arg check: --check "Report unformatted files instead of rewriting them")
+            (@arg paths: <PATH>... "Files to format, or - to format stdin to stdout")
+        )
     )
     .get_matches();
 
-    let path = matches.value_of("file");
-    let code = matches.value_of("code");
-    let code = match (path, code) {
-        (Some(path), _) => read_file(path).ok(),
-        (_, Some(code)) => Some(code.to_owned()),
-        _ => None,
+    match matches.subcommand() {
+        ("fmt", Some(matches)) => fmt(matches),
+        _ => run(&matches),
+    }
+}
+
+fn run(matches: &ArgMatches) {
+    let inline = matches.value_of("code").or(matches.value_of("expression"));
+    let print_result = matches.is_present("print_result") || matches.is_present("expression");
+
+    // With -f, -c or -e, every positional argument belongs to the script.
+    let mut positional = matches
+        .value_of("script")
+        .into_iter()
+        .chain(matches.values_of("args").into_iter().flatten());
+    let path = match (matches.value_of("file"), inline) {
+        (Some(path), _) => Some(path),
+        (None, Some(_)) => None,
+        (None, None) => positional.next(),
+    };
+    let args = positional
+        .map(|arg| Object::String(arg.to_owned()))
+        .collect();
+
+    let mut builder = Evaluator::builder();
+    let code = match (path, inline) {
+        (Some(path), _) => match read_source(path) {
+            Ok(code) => {
+                if path != "-" {
+                    builder = builder.path(path);
+                }
+                code
+            }
+            Err(e) => {
+                eprintln!("error: cannot read {}: {}", path, e);
+                process::exit(INPUT_ERROR);
+            }
+        },
+        (_, Some(code)) => code.to_owned(),
+        _ => return,
     };
+    let mut evaluator = builder
+        .capabilities(Capabilities::all())
+        .global("args", Object::Array(Array(args)))
+        .build()
+        .expect("the standard prelude is valid");
+
+    match evaluator.eval_code(&code) {
+        Ok(Object::Error(e)) => {
+            eprintln!("error: {}", e);
+            process::exit(RUNTIME_ERROR);
+        }
+        Ok(Object::Exit(code)) => process::exit(code),
+        Ok(object) if print_result => println!("{}", object),
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("{}", parse_error(&code, e));
+            process::exit(PARSE_ERROR);
+        }
+    }
+}
 
-    if let Some(code) = code {
-        match Evaluator::new().eval_code(&code) {
-            Ok(object) => println!("{}", object),
-            Err(nom::Err::Error(_)) => println!("Parser error"),
-            Err(nom::Err::Failure(_)) => println!("Parser failure"),
-            Err(nom::Err::Incomplete(_)) => println!("Incomplete parsing"),
+fn fmt(matches: &ArgMatches) {
+    let check = matches.is_present("check");
+    let mut status = 0;
+    for path in matches.values_of("paths").into_iter().flatten() {
+        let code = match read_source(path) {
+            Ok(code) => code,
+            Err(e) => {
+                eprintln!("error: cannot read {}: {}", path, e);
+                status = INPUT_ERROR;
+                continue;
+            }
+        };
+        let formatted = match format(&code) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                eprintln!("{}: {}", path, parse_error(&code, e));
+                status = PARSE_ERROR;
+                continue;
+            }
+        };
+        if check {
+            if formatted != code {
+                eprintln!("{} is not formatted", path);
+                status = UNFORMATTED;
+            }
+        } else if path == "-" {
+            print!("{}", formatted);
+        } else if formatted != code {
+            if let Err(e) = fs::write(path, formatted) {
+                eprintln!("error: cannot write {}: {}", path, e);
+                status = OUTPUT_ERROR;
+            }
         }
     }
+    process::exit(status);
 }
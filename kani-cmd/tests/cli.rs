@@ -0,0 +1,108 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+/// A script written to the temporary directory, removed when dropped.
+struct Script(PathBuf);
+
+impl Script {
+    fn new(name: &str, code: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("kani-cli-{}-{}.kn", name, std::process::id()));
+        fs::write(&path, code).unwrap();
+        Script(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn kani(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kani"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn assert_output(output: Output, status: i32, stdout: &str, stderr: &str) {
+    assert_eq!(String::from_utf8_lossy(&output.stdout), stdout);
+    assert_eq!(String::from_utf8_lossy(&output.stderr), stderr);
+    assert_eq!(output.status.code(), Some(status));
+}
+
+#[test]
+fn print_result_test() {
+    assert_output(kani(&["-e", "1 + 2"], ""), 0, "3\n", "");
+    assert_output(kani(&["-c", "1 + 2"], ""), 0, "", "");
+    assert_output(kani(&["-c", "1 + 2", "-p"], ""), 0, "3\n", "");
+    assert_output(kani(&["-e", "print(1)"], ""), 0, "1\nnull\n", "");
+}
+
+#[test]
+fn error_status_test() {
+    assert_output(
+        kani(&["-c", "x = "], ""),
+        65,
+        "",
+        "parse error at line 1, column 5\n",
+    );
+    let output = kani(&["kani-cli-missing.kn"], "");
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .starts_with("error: cannot read kani-cli-missing.kn: "));
+    assert_eq!(output.status.code(), Some(66));
+    assert_output(
+        kani(&["-c", "print(1); missing; print(2)"], ""),
+        70,
+        "1\n",
+        "error: identifier not found: missing\n",
+    );
+}
+
+#[test]
+fn exit_test() {
+    assert_output(
+        kani(&["-c", "print(1); exit(4); print(2)"], ""),
+        4,
+        "1\n",
+        "",
+    );
+    assert_output(kani(&["-e", "exit(0); 1"], ""), 0, "", "");
+}
+
+#[test]
+fn args_test() {
+    let script = Script::new("args", "print(args)");
+    assert_output(kani(&[script.path(), "a", "-b"], ""), 0, "[a, -b]\n", "");
+    assert_output(kani(&["-f", script.path(), "a"], ""), 0, "[a]\n", "");
+    assert_output(kani(&["-e", "args", "a", "b"], ""), 0, "[a, b]\n", "");
+    assert_output(kani(&["-e", "len(args)"], ""), 0, "0\n", "");
+}
+
+#[test]
+fn stdin_test() {
+    assert_output(kani(&["-", "x"], "print(args)"), 0, "[x]\n", "");
+    assert_output(
+        kani(&["-"], "x = "),
+        65,
+        "",
+        "parse error at line 1, column 5\n",
+    );
+}
@@ -0,0 +1,191 @@
+use crate::builtin::BuiltinModule;
+use crate::context::{Capabilities, Context, Limits};
+use crate::environment::Environment;
+use crate::object::Object;
+use crate::output::{Sink, Stderr, Stdout};
+use crate::prelude::Prelude;
+use crate::Evaluator;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// Configures an [`Evaluator`]. The defaults install every builtin module and
+/// the standard prelude, with no limits and no access to the host system,
+/// writing to the process stdout and stderr.
+pub struct EvaluatorBuilder {
+    path: Option<PathBuf>,
+    prelude: Prelude,
+    modules: Vec<BuiltinModule>,
+    limits: Limits,
+    capabilities: Capabilities,
+    globals: Vec<(String, Object)>,
+    stdout: Box<dyn Sink>,
+    stderr: Box<dyn Sink>,
+}
+
+impl Default for EvaluatorBuilder {
+    fn default() -> Self {
+        Self {
+            path: None,
+            prelude: Prelude::default(),
+            modules: BuiltinModule::ALL.to_vec(),
+            limits: Limits::default(),
+            capabilities: Capabilities::default(),
+            globals: Vec::new(),
+            stdout: Box::new(Stdout),
+            stderr: Box::new(Stderr),
+        }
+    }
+}
+
+impl EvaluatorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the path of the script being run, used to resolve its imports.
+    pub fn path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn prelude(mut self, prelude: Prelude) -> Self {
+        self.prelude = prelude;
+        self
+    }
+
+    /// Installs only the given builtin modules. Most of the standard prelude
+    /// calls into `Core` and `List`.
+    pub fn modules(mut self, modules: &[BuiltinModule]) -> Self {
+        self.modules = modules.to_vec();
+        self
+    }
+
+    pub fn max_call_depth(mut self, depth: usize) -> Self {
+        self.limits.max_call_depth = Some(depth);
+        self
+    }
+
+    /// Grants the IO builtins access to the host system.
+    pub fn capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Sets where `print` writes to.
+    pub fn stdout<S: Sink + 'static>(mut self, sink: S) -> Self {
+        self.stdout = Box::new(sink);
+        self
+    }
+
+    /// Sets where `eprint` writes to.
+    pub fn stderr<S: Sink + 'static>(mut self, sink: S) -> Self {
+        self.stderr = Box::new(sink);
+        self
+    }
+
+    /// Binds `name` in the global scope, after the prelude.
+    pub fn global(mut self, name: &str, object: Object) -> Self {
+        self.globals.push((name.to_string(), object));
+        self
+    }
+
+    /// Fails if the prelude does not parse or evaluates to an error.
+    pub fn build(self) -> Result<Evaluator, String> {
+        let builtins = self.modules.iter().flat_map(|m| m.builtins()).collect();
+        let context = Context::new(self.limits, self.capabilities, self.stdout, self.stderr);
+        let context = Rc::new(context);
+        let globals = Environment::with_builtins(builtins, Rc::clone(&context));
+        let globals = Rc::new(RefCell::new(globals));
+        *context.globals.borrow_mut() = Rc::downgrade(&globals);
+        self.prelude.load(&globals)?;
+        for (name, object) in self.globals {
+            globals.borrow_mut().set(&name, object);
+        }
+        let mut env = Environment::from(globals);
+        if let Some(path) = self.path {
+            env = env.with_path(path);
+        }
+        Ok(Evaluator::from(env))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Buffer;
+
+    #[test]
+    fn modules_test() {
+        let mut evaluator = Evaluator::builder()
+            .modules(&[BuiltinModule::Core, BuiltinModule::List])
+            .build()
+            .unwrap();
+        assert_eq!(
+            evaluator.eval_code("sum([1, 2])").unwrap(),
+            Object::Integer(3)
+        );
+        assert_eq!(
+            evaluator.eval_code("upper(\"a\")").unwrap(),
+            Object::Error("identifier not found: upper".to_string())
+        );
+        let mut evaluator = Evaluator::builder()
+            .modules(&[BuiltinModule::Core])
+            .build()
+            .unwrap();
+        assert_eq!(
+            evaluator.eval_code("sum([1, 2])").unwrap(),
+            Object::Error("identifier not found: fold".to_string())
+        );
+    }
+
+    #[test]
+    fn globals_test() {
+        let mut evaluator = Evaluator::builder()
+            .global("version", Object::String("1.0".to_string()))
+            .global("identity", Object::Integer(0))
+            .build()
+            .unwrap();
+        assert_eq!(
+            evaluator.eval_code("version").unwrap(),
+            Object::String("1.0".to_string())
+        );
+        assert_eq!(evaluator.eval_code("identity").unwrap(), Object::Integer(0));
+    }
+
+    #[test]
+    fn max_call_depth_test() {
+        let mut evaluator = Evaluator::builder().max_call_depth(50).build().unwrap();
+        let code = "f = |n| if n == 0 then 0 else 1 + f(n - 1);";
+        assert_eq!(
+            evaluator.eval_code(&format!("{} f(10)", code)).unwrap(),
+            Object::Integer(10)
+        );
+        assert_eq!(
+            evaluator.eval_code("f(100)").unwrap(),
+            Object::Error("maximum call depth of 50 exceeded".to_string())
+        );
+        assert_eq!(evaluator.eval_code("f(20)").unwrap(), Object::Integer(20));
+    }
+
+    #[test]
+    fn output_test() {
+        let stdout = Buffer::new();
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&errors);
+        let mut evaluator = Evaluator::builder()
+            .stdout(stdout.clone())
+            .stderr(move |line: &str| sink.borrow_mut().push(line.to_string()))
+            .build()
+            .unwrap();
+        let code = "print(\"kani\"); print([1, 2].map(|x| x * 2)); eprint(\"oops\")";
+        assert_eq!(evaluator.eval_code(code).unwrap(), Object::Null);
+        assert_eq!(stdout.contents(), "kani\n[2, 4]\n");
+        assert_eq!(*errors.borrow(), vec!["oops".to_string()]);
+        assert_eq!(
+            evaluator.eval_code("print(missing)").unwrap(),
+            Object::Error("identifier not found: missing".to_string())
+        );
+        assert_eq!(stdout.contents(), "kani\n[2, 4]\n");
+    }
+}
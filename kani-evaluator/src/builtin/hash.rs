@@ -0,0 +1,186 @@
+//! Hash builtins are persistent: `insert`, `remove` and `merge` return a new
+//! hash and leave their arguments untouched.
+
+use crate::object::*;
+use indexmap::IndexMap;
+
+pub fn builtins() -> Vec<Builtin> {
+    vec![
+        keys(),
+        values(),
+        entries(),
+        has(),
+        get_or(),
+        insert(),
+        remove(),
+        merge(),
+        from_entries(),
+    ]
+}
+
+fn keys() -> Builtin {
+    Builtin::new("keys", 1, |args, _| match args.as_slice() {
+        [Object::Hash(hash)] => Ok(Object::Array(Array(
+            hash.iter().map(|(k, _)| k.clone()).collect(),
+        ))),
+        _ => Err("invalid arguments for keys".to_string()),
+    })
+}
+
+fn values() -> Builtin {
+    Builtin::new("values", 1, |args, _| match args.as_slice() {
+        [Object::Hash(hash)] => Ok(Object::Array(Array(
+            hash.iter().map(|(_, v)| v.clone()).collect(),
+        ))),
+        _ => Err("invalid arguments for values".to_string()),
+    })
+}
+
+fn entries() -> Builtin {
+    Builtin::new("entries", 1, |args, _| match args.as_slice() {
+        [Object::Hash(hash)] => Ok(Object::Array(Array(
+            hash.iter()
+                .map(|(k, v)| Object::Array(Array(vec![k.clone(), v.clone()])))
+                .collect(),
+        ))),
+        _ => Err("invalid arguments for entries".to_string()),
+    })
+}
+
+fn has() -> Builtin {
+    Builtin::new("has", 2, |args, _| match args.as_slice() {
+        [key, Object::Hash(hash)] => {
+            let key = key.clone().hash_key()?;
+            Ok(Object::Boolean(hash.contains_key(&key)))
+        }
+        _ => Err("invalid arguments for has".to_string()),
+    })
+}
+
+fn get_or() -> Builtin {
+    Builtin::new("get_or", 3, |args, _| match args.as_slice() {
+        [key, default, Object::Hash(hash)] => {
+            let key = key.clone().hash_key()?;
+            Ok(hash.get(&key).unwrap_or(default).clone())
+        }
+        _ => Err("invalid arguments for get_or".to_string()),
+    })
+}
+
+fn insert() -> Builtin {
+    Builtin::new("insert", 3, |args, _| match args.as_slice() {
+        [key, value, Object::Hash(Hash(hash))] => {
+            let mut hash = hash.clone();
+            hash.insert(key.clone().hash_key()?, value.clone());
+            Ok(Object::Hash(Hash(hash)))
+        }
+        _ => Err("invalid arguments for insert".to_string()),
+    })
+}
+
+fn remove() -> Builtin {
+    Builtin::new("remove", 2, |args, _| match args.as_slice() {
+        [key, Object::Hash(Hash(hash))] => {
+            let mut hash = hash.clone();
+            hash.shift_remove(&key.clone().hash_key()?);
+            Ok(Object::Hash(Hash(hash)))
+        }
+        _ => Err("invalid arguments for remove".to_string()),
+    })
+}
+
+fn merge() -> Builtin {
+    Builtin::new("merge", 2, |args, _| match args.as_slice() {
+        [Object::Hash(Hash(l)), Object::Hash(Hash(r))] => {
+            let mut hash = l.clone();
+            hash.extend(r.iter().map(|(k, v)| (k.clone(), v.clone())));
+            Ok(Object::Hash(Hash(hash)))
+        }
+        _ => Err("invalid arguments for merge".to_string()),
+    })
+}
+
+fn from_entries() -> Builtin {
+    Builtin::new("from_entries", 1, |args, _| match args.as_slice() {
+        [Object::Array(arr)] => {
+            let mut hash = IndexMap::new();
+            for entry in arr.iter() {
+                match entry {
+                    Object::Array(Array(pair)) if pair.len() == 2 => {
+                        hash.insert(pair[0].clone().hash_key()?, pair[1].clone());
+                    }
+                    o => return Err(format!("{} is not an entry", o)),
+                }
+            }
+            Ok(Object::Hash(Hash(hash)))
+        }
+        _ => Err("invalid arguments for from_entries".to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::object::*;
+    use crate::test_util::eval;
+
+    #[test]
+    fn read_test() {
+        let h = "h = {\"b\": 2, \"a\": 1, 3: \"c\"};";
+        assert_eq!(eval(&format!("{} keys(h)", h)), eval("[\"b\", \"a\", 3]"));
+        assert_eq!(eval(&format!("{} values(h)", h)), eval("[2, 1, \"c\"]"));
+        assert_eq!(
+            eval(&format!("{} entries(h)", h)),
+            eval("[[\"b\", 2], [\"a\", 1], [3, \"c\"]]")
+        );
+        assert_eq!(eval(&format!("{} has(\"a\", h)", h)), Object::Boolean(true));
+        assert_eq!(
+            eval(&format!("{} has(\"z\", h)", h)),
+            Object::Boolean(false)
+        );
+        assert_eq!(
+            eval(&format!("{} get_or(\"z\", 0, h)", h)),
+            Object::Integer(0)
+        );
+        assert_eq!(eval(&format!("{} len(h)", h)), Object::Integer(3));
+    }
+
+    #[test]
+    fn persistent_test() {
+        let h = "h = {\"a\": 1};";
+        assert_eq!(
+            eval(&format!("{} insert(\"b\", 2, h)", h)),
+            eval("{\"a\": 1, \"b\": 2}")
+        );
+        assert_eq!(eval(&format!("{} insert(\"b\", 2, h); h", h)), eval(h));
+        assert_eq!(eval(&format!("{} remove(\"a\", h)", h)), eval("{}"));
+        assert_eq!(eval(&format!("{} remove(\"a\", h); h", h)), eval(h));
+        assert_eq!(
+            eval(&format!("{} merge(h, {{\"a\": 2, \"c\": 3}})", h)),
+            eval("{\"a\": 2, \"c\": 3}")
+        );
+        assert_eq!(
+            eval("from_entries([[\"a\", 1], [true, 2]])"),
+            eval("{\"a\": 1, true: 2}")
+        );
+    }
+
+    #[test]
+    fn order_test() {
+        assert_eq!(
+            format!("{}", eval("{\"b\": 2, 1: \"x\", \"a\": 1, false: 0}")),
+            "{b : 2, 1 : x, a : 1, false : 0}"
+        );
+        assert_eq!(
+            format!("{}", eval("remove(\"b\", {\"a\": 1, \"b\": 2, \"c\": 3})")),
+            "{a : 1, c : 3}"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                eval("merge({\"a\": 1, \"b\": 2}, {\"c\": 3, \"a\": 4})")
+            ),
+            "{a : 4, b : 2, c : 3}"
+        );
+        assert_ne!(eval("{\"a\": 1, \"b\": 2}"), eval("{\"b\": 2, \"a\": 1}"));
+    }
+}
@@ -0,0 +1,397 @@
+//! Everything here except `print` and `eprint` touches the host system, so it
+//! is checked against the evaluator's [`Capabilities`], which deny it all by
+//! default.
+//!
+//! [`Capabilities`]: crate::context::Capabilities
+
+use crate::object::*;
+use std::convert::TryFrom;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+pub fn builtins() -> Vec<Builtin> {
+    vec![
+        print(),
+        eprint(),
+        read_file(),
+        write_file(),
+        list_dir(),
+        read_line(),
+        stdin(),
+        env(),
+        exit(),
+    ]
+}
+
+fn print() -> Builtin {
+    Builtin::new("print", 1, |args, context| match args.as_slice() {
+        [o] => {
+            context.print(&format!("{}", o));
+            Ok(Object::Null)
+        }
+        _ => Err("invalid arguments for print".to_string()),
+    })
+}
+
+fn eprint() -> Builtin {
+    Builtin::new("eprint", 1, |args, context| match args.as_slice() {
+        [o] => {
+            context.eprint(&format!("{}", o));
+            Ok(Object::Null)
+        }
+        _ => Err("invalid arguments for eprint".to_string()),
+    })
+}
+
+fn read_file() -> Builtin {
+    Builtin::new("read_file", 1, |args, context| match args.as_slice() {
+        [Object::String(path)] => {
+            let path = allowed(path, &context.capabilities.read, "read")?;
+            fs::read_to_string(&path)
+                .map(Object::String)
+                .map_err(|e| format!("cannot read {}: {}", path.display(), e))
+        }
+        _ => Err("invalid arguments for read_file".to_string()),
+    })
+}
+
+fn write_file() -> Builtin {
+    Builtin::new("write_file", 2, |args, context| match args.as_slice() {
+        [Object::String(path), Object::String(contents)] => {
+            let path = allowed(path, &context.capabilities.write, "write")?;
+            write(&path, contents)
+                .map(|_| Object::Null)
+                .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+        }
+        _ => Err("invalid arguments for write_file".to_string()),
+    })
+}
+
+fn list_dir() -> Builtin {
+    Builtin::new("list_dir", 1, |args, context| match args.as_slice() {
+        [Object::String(path)] => {
+            let path = allowed(path, &context.capabilities.read, "read")?;
+            let error = |e: io::Error| format!("cannot list {}: {}", path.display(), e);
+            let mut names = fs::read_dir(&path)
+                .map_err(error)?
+                .map(|entry| entry.map(|e| e.file_name().to_string_lossy().into_owned()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(error)?;
+            names.sort();
+            Ok(Object::Array(Array(
+                names.into_iter().map(Object::String).collect(),
+            )))
+        }
+        _ => Err("invalid arguments for list_dir".to_string()),
+    })
+}
+
+/// Returns the next line of stdin without its line break, or null at the end.
+fn read_line() -> Builtin {
+    Builtin::new("read_line", 0, |args, context| match args.as_slice() {
+        [] => {
+            require(context.capabilities.stdin, "reading stdin")?;
+            let mut line = String::new();
+            match io::stdin().read_line(&mut line) {
+                Ok(0) => Ok(Object::Null),
+                Ok(_) => {
+                    let len = line.trim_end_matches(&['\r', '\n'][..]).len();
+                    line.truncate(len);
+                    Ok(Object::String(line))
+                }
+                Err(e) => Err(format!("cannot read stdin: {}", e)),
+            }
+        }
+        _ => Err("invalid arguments for read_line".to_string()),
+    })
+}
+
+fn stdin() -> Builtin {
+    Builtin::new("stdin", 0, |args, context| match args.as_slice() {
+        [] => {
+            require(context.capabilities.stdin, "reading stdin")?;
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map(|_| Object::String(input))
+                .map_err(|e| format!("cannot read stdin: {}", e))
+        }
+        _ => Err("invalid arguments for stdin".to_string()),
+    })
+}
+
+fn env() -> Builtin {
+    Builtin::new("env", 1, |args, context| match args.as_slice() {
+        [Object::String(name)] => {
+            require(context.capabilities.env, "reading environment variables")?;
+            Ok(std::env::var(name).map_or(Object::Null, Object::String))
+        }
+        _ => Err("invalid arguments for env".to_string()),
+    })
+}
+
+/// Stops the program with the given status. The host ends the process, so an
+/// embedding application only sees an [`Object::Exit`].
+fn exit() -> Builtin {
+    Builtin::new("exit", 1, |args, context| match args.as_slice() {
+        [Object::Integer(code)] => {
+            require(context.capabilities.exit, "exit")?;
+            i32::try_from(*code)
+                .map(Object::Exit)
+                .map_err(|_| format!("exit status out of range: {}", code))
+        }
+        _ => Err("invalid arguments for exit".to_string()),
+    })
+}
+
+fn require(allowed: bool, what: &str) -> Result<(), String> {
+    match allowed {
+        true => Ok(()),
+        false => Err(format!("permission denied: {}", what)),
+    }
+}
+
+/// Writes to `path` without following a symlink that appeared there after
+/// [`allowed`] checked it: a new file is created exclusively, and an existing
+/// one is only truncated once it is known not to be a link.
+fn write(path: &Path, contents: &str) -> io::Result<()> {
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            OpenOptions::new().write(true).open(path)?
+        }
+        file => file?,
+    };
+    if fs::symlink_metadata(path)?.file_type().is_symlink() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "refusing to follow a symbolic link",
+        ));
+    }
+    file.set_len(0)?;
+    file.write_all(contents.as_bytes())
+}
+
+/// Resolves `path` and checks that it lies inside one of `dirs`. A file that
+/// does not exist yet is checked through its parent directory, and a dangling
+/// symlink is refused, since writing to it would create its target.
+pub(crate) fn allowed(path: &str, dirs: &[PathBuf], access: &str) -> Result<PathBuf, String> {
+    let denied = || format!("permission denied: cannot {} {}", access, path);
+    let path = Path::new(path);
+    let resolved = match (path.canonicalize(), path.parent(), path.file_name()) {
+        (Ok(path), _, _) => path,
+        (Err(_), _, _) if path.symlink_metadata().is_ok() => return Err(denied()),
+        (Err(_), Some(parent), Some(name)) => {
+            let parent = match parent.as_os_str().is_empty() {
+                true => Path::new("."),
+                false => parent,
+            };
+            parent.canonicalize().map_err(|_| denied())?.join(name)
+        }
+        _ => return Err(denied()),
+    };
+    let inside = |dir: &PathBuf| {
+        dir.canonicalize()
+            .is_ok_and(|dir| resolved.starts_with(dir))
+    };
+    match dirs.iter().any(inside) {
+        true => Ok(resolved),
+        false => Err(denied()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Capabilities;
+    use crate::object::*;
+    use crate::test_util::{capture, eval, eval_with, TempDir};
+    use std::fs;
+
+    /// A temporary directory with a config file.
+    fn sandbox(name: &str) -> TempDir {
+        TempDir::new(
+            &format!("io-{}", name),
+            &[("data/config.txt", "debug = true")],
+        )
+    }
+
+    fn error(e: &str) -> Object {
+        Object::Error(e.to_string())
+    }
+
+    #[test]
+    fn denied_test() {
+        let dir = sandbox("denied");
+        let file = dir.join("data/config.txt");
+        let code = format!("read_file(\"{}\")", file.display());
+        assert_eq!(
+            eval_with(Capabilities::default(), &code),
+            error(&format!(
+                "permission denied: cannot read {}",
+                file.display()
+            ))
+        );
+        assert_eq!(
+            eval_with(Capabilities::default(), "env(\"PATH\")"),
+            error("permission denied: reading environment variables")
+        );
+        assert_eq!(
+            eval_with(Capabilities::default(), "read_line()"),
+            error("permission denied: reading stdin")
+        );
+        assert_eq!(
+            eval_with(Capabilities::default(), "exit(1)"),
+            error("permission denied: exit")
+        );
+    }
+
+    #[test]
+    fn read_only_test() {
+        let dir = sandbox("read-only");
+        let capabilities = Capabilities {
+            read: vec![dir.join("data")],
+            ..Capabilities::default()
+        };
+        let file = dir.join("data/config.txt");
+        assert_eq!(
+            eval_with(
+                capabilities.clone(),
+                &format!("read_file(\"{}\")", file.display())
+            ),
+            Object::String("debug = true".to_string())
+        );
+        assert_eq!(
+            eval_with(
+                capabilities.clone(),
+                &format!("list_dir(\"{}\")", dir.join("data").display())
+            ),
+            eval("[\"config.txt\"]")
+        );
+        let outside = dir.join("data/../secret.txt");
+        fs::write(&outside, "").unwrap();
+        assert_eq!(
+            eval_with(
+                capabilities.clone(),
+                &format!("read_file(\"{}\")", outside.display())
+            ),
+            error(&format!(
+                "permission denied: cannot read {}",
+                outside.display()
+            ))
+        );
+        let report = dir.join("data/report.txt");
+        assert_eq!(
+            eval_with(
+                capabilities,
+                &format!("write_file(\"{}\", \"ok\")", report.display())
+            ),
+            error(&format!(
+                "permission denied: cannot write {}",
+                report.display()
+            ))
+        );
+    }
+
+    #[test]
+    fn write_test() {
+        let dir = sandbox("write");
+        let capabilities = Capabilities {
+            read: vec![dir.clone()],
+            write: vec![dir.join("out")],
+            ..Capabilities::default()
+        };
+        fs::create_dir_all(dir.join("out")).unwrap();
+        let report = dir.join("out/report.txt");
+        let code = format!(
+            "write_file(\"{0}\", \"ok\"); read_file(\"{0}\")",
+            report.display()
+        );
+        assert_eq!(
+            eval_with(capabilities, &code),
+            Object::String("ok".to_string())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_test() {
+        let dir = sandbox("symlink");
+        let capabilities = Capabilities {
+            write: vec![dir.join("data")],
+            ..Capabilities::default()
+        };
+        let outside = dir.join("outside.txt");
+        let link = dir.join("data/link");
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+        assert_eq!(
+            eval_with(
+                capabilities,
+                &format!("write_file(\"{}\", \"escaped\")", link.display())
+            ),
+            error(&format!(
+                "permission denied: cannot write {}",
+                link.display()
+            ))
+        );
+        assert!(!outside.exists());
+    }
+
+    #[test]
+    fn process_test() {
+        std::env::set_var("KANI_IO_TEST", "crab");
+        let capabilities = Capabilities {
+            env: true,
+            ..Capabilities::default()
+        };
+        assert_eq!(
+            eval_with(capabilities.clone(), "env(\"KANI_IO_TEST\")"),
+            Object::String("crab".to_string())
+        );
+        assert_eq!(
+            eval_with(capabilities, "env(\"KANI_IO_MISSING\")"),
+            Object::Null
+        );
+    }
+
+    #[test]
+    fn exit_test() {
+        let capabilities = Capabilities {
+            exit: true,
+            ..Capabilities::default()
+        };
+        let (mut evaluator, stdout) = capture(capabilities.clone());
+        assert_eq!(
+            evaluator.eval_code("print(1); exit(3); print(2)").unwrap(),
+            Object::Exit(3)
+        );
+        assert_eq!(stdout.contents(), "1\n");
+        assert_eq!(
+            eval_with(
+                capabilities.clone(),
+                "check = |x| { if x < 0 then exit(2); x }; check(-1); 0"
+            ),
+            Object::Exit(2)
+        );
+        assert_eq!(
+            eval_with(capabilities, "exit(4294967296)"),
+            error("exit status out of range: 4294967296")
+        );
+    }
+
+    #[test]
+    fn exit_argument_test() {
+        let (mut evaluator, stdout) = capture(Capabilities {
+            exit: true,
+            ..Capabilities::default()
+        });
+        let mut eval = |code| evaluator.eval_code(code).unwrap();
+        assert_eq!(eval("print(exit(3)); print(1)"), Object::Exit(3));
+        assert_eq!(eval("f = |x| 1; f(exit(4))"), Object::Exit(4));
+        assert_eq!(eval("map(|x| exit(9), [1, 2])"), Object::Exit(9));
+        assert_eq!(eval("any(|x| exit(9), [1, 2])"), Object::Exit(9));
+        assert_eq!(eval("\"${exit(3)}\""), Object::Exit(3));
+        assert_eq!(eval("[1, exit(5)]"), Object::Exit(5));
+        assert_eq!(eval("{\"a\": exit(6)}"), Object::Exit(6));
+        assert_eq!(stdout.contents(), "");
+    }
+}
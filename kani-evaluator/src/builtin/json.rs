@@ -0,0 +1,255 @@
+//! JSON objects become hashes with string keys. Numbers without a fraction or
+//! exponent become integers when they fit.
+
+use crate::nom;
+use crate::nom::branch::alt;
+use crate::nom::bytes::complete::{tag, take_while_m_n};
+use crate::nom::character::complete::{char, digit1, multispace0, one_of};
+use crate::nom::combinator::{all_consuming, map, map_opt, map_res, opt, recognize, value, verify};
+use crate::nom::error::{Error, ErrorKind};
+use crate::nom::multi::separated_list0;
+use crate::nom::sequence::{delimited, pair, preceded, separated_pair, tuple};
+use crate::nom::IResult;
+use crate::object::*;
+use indexmap::IndexMap;
+
+pub fn builtins() -> Vec<Builtin> {
+    vec![to_json(), parse_json()]
+}
+
+fn to_json() -> Builtin {
+    Builtin::new("to_json", 1, |args, _| match args.as_slice() {
+        [o] => {
+            let mut json = String::new();
+            write(o, &mut json)?;
+            Ok(Object::String(json))
+        }
+        _ => Err("invalid arguments for to_json".to_string()),
+    })
+}
+
+fn parse_json() -> Builtin {
+    Builtin::new("parse_json", 1, |args, _| match args.as_slice() {
+        [Object::String(s)] => match all_consuming(json)(s) {
+            Ok((_, o)) => Ok(o),
+            Err(_) => Err("invalid json".to_string()),
+        },
+        _ => Err("invalid arguments for parse_json".to_string()),
+    })
+}
+
+/// Integer and boolean hash keys are written as strings, as JSON requires.
+fn write(object: &Object, json: &mut String) -> Result<(), String> {
+    match object {
+        Object::Integer(i) => json.push_str(&i.to_string()),
+        Object::Float(f) if f.is_finite() => json.push_str(&format!("{:?}", f)),
+        Object::Boolean(b) => json.push_str(&b.to_string()),
+        Object::String(s) => write_str(s, json),
+        Object::Null => json.push_str("null"),
+        Object::Array(arr) => {
+            json.push('[');
+            for (i, o) in arr.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write(o, json)?;
+            }
+            json.push(']');
+        }
+        Object::Hash(hash) => {
+            json.push('{');
+            for (i, (k, v)) in hash.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                match k {
+                    Object::String(s) => write_str(s, json),
+                    k => write_str(&k.to_string(), json),
+                }
+                json.push(':');
+                write(v, json)?;
+            }
+            json.push('}');
+        }
+        o => return Err(format!("cannot convert {} to json", o)),
+    }
+    Ok(())
+}
+
+fn write_str(s: &str, json: &mut String) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c < ' ' => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+fn json(input: &str) -> IResult<&str, Object> {
+    delimited(
+        multispace0,
+        alt((
+            value(Object::Null, tag("null")),
+            value(Object::Boolean(true), tag("true")),
+            value(Object::Boolean(false), tag("false")),
+            number,
+            map(string, Object::String),
+            array,
+            object,
+        )),
+        multispace0,
+    )(input)
+}
+
+fn number(input: &str) -> IResult<&str, Object> {
+    let (rest, number) = recognize(tuple((
+        opt(char('-')),
+        alt((tag("0"), digit1)),
+        opt(pair(char('.'), digit1)),
+        opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+    )))(input)?;
+    let integer = !number.contains(&['.', 'e', 'E'][..]);
+    match (integer, number.parse()) {
+        (true, Ok(i)) => Ok((rest, Object::Integer(i))),
+        _ => match number.parse() {
+            Ok(f) => Ok((rest, Object::Float(f))),
+            Err(_) => Err(nom::Err::Error(Error::new(input, ErrorKind::Float))),
+        },
+    }
+}
+
+fn string(input: &str) -> IResult<&str, String> {
+    let (mut rest, _) = char('"')(input)?;
+    let mut s = String::new();
+    loop {
+        let mut chars = rest.chars();
+        match chars.next() {
+            Some('"') => return Ok((chars.as_str(), s)),
+            Some('\\') => {
+                let (next, c) = escaped_char(chars.as_str())?;
+                s.push(c);
+                rest = next;
+            }
+            Some(c) if c >= ' ' => {
+                s.push(c);
+                rest = chars.as_str();
+            }
+            _ => return Err(nom::Err::Error(Error::new(rest, ErrorKind::Char))),
+        }
+    }
+}
+
+fn escaped_char(input: &str) -> IResult<&str, char> {
+    alt((
+        unicode,
+        value('\n', char('n')),
+        value('\r', char('r')),
+        value('\t', char('t')),
+        value('\u{08}', char('b')),
+        value('\u{0C}', char('f')),
+        value('\\', char('\\')),
+        value('/', char('/')),
+        value('"', char('"')),
+    ))(input)
+}
+
+/// Characters outside the basic multilingual plane are written as a pair of
+/// UTF-16 surrogates.
+fn unicode(input: &str) -> IResult<&str, char> {
+    let (rest, high) = hex(input)?;
+    if !(0xD800..0xDC00).contains(&high) {
+        return map_opt(hex, std::char::from_u32)(input);
+    }
+    let low = verify(preceded(char('\\'), hex), |low| {
+        (0xDC00..0xE000).contains(low)
+    });
+    map_opt(low, move |low| {
+        std::char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+    })(rest)
+}
+
+fn hex(input: &str) -> IResult<&str, u32> {
+    let digits = take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit());
+    map_res(preceded(char('u'), digits), |hex| {
+        u32::from_str_radix(hex, 16)
+    })(input)
+}
+
+fn array(input: &str) -> IResult<&str, Object> {
+    map(
+        delimited(
+            char('['),
+            separated_list0(char(','), json),
+            preceded(multispace0, char(']')),
+        ),
+        |arr| Object::Array(Array(arr)),
+    )(input)
+}
+
+fn object(input: &str) -> IResult<&str, Object> {
+    let key = delimited(multispace0, string, multispace0);
+    map(
+        delimited(
+            char('{'),
+            separated_list0(char(','), separated_pair(key, char(':'), json)),
+            preceded(multispace0, char('}')),
+        ),
+        |entries| {
+            let hash: IndexMap<_, _> = entries
+                .into_iter()
+                .map(|(k, v)| (Object::String(k), v))
+                .collect();
+            Object::Hash(Hash(hash))
+        },
+    )(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::object::*;
+    use crate::test_util::eval;
+
+    #[test]
+    fn to_json_test() {
+        assert_eq!(
+            eval("to_json({a: [1, 2.5, true], b: \"say \\\"hi\\\"\\n\"})"),
+            Object::String("{\"a\":[1,2.5,true],\"b\":\"say \\\"hi\\\"\\n\"}".to_string())
+        );
+        assert_eq!(
+            eval("to_json({1: 1.0})"),
+            Object::String("{\"1\":1.0}".to_string())
+        );
+        assert_eq!(
+            eval("to_json(len)"),
+            Object::Error("cannot convert [built-in function: len] to json".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_json_test() {
+        assert_eq!(
+            eval("parse_json(\" {\\\"a\\\": [1, -2.5e1, true], \\\"b\\\": {}} \")"),
+            eval("{a: [1, -25.0, true], b: {}}")
+        );
+        assert_eq!(eval("parse_json(\"null\")"), Object::Null);
+        assert_eq!(
+            eval("parse_json(\"\\\"\\\\u00e9\\\\ud83e\\\\udd80\\\"\")"),
+            Object::String("é🦀".to_string())
+        );
+        assert_eq!(
+            eval("parse_json(\"[1, 2\")"),
+            Object::Error("invalid json".to_string())
+        );
+        assert_eq!(
+            eval("x = {a: [1, \"b\"], c: 1.5}; parse_json(to_json(x)) == x"),
+            Object::Boolean(true)
+        );
+    }
+}
@@ -0,0 +1,406 @@
+use super::string::index_of;
+use crate::evaluator::apply_all;
+use crate::object::*;
+use indexmap::IndexMap;
+use std::cmp::Ordering;
+
+const MAX_LEN: usize = 1 << 24;
+
+pub fn builtins() -> Vec<Builtin> {
+    vec![
+        map(),
+        filter(),
+        reduce(),
+        fold(),
+        range(),
+        zip(),
+        enumerate(),
+        sort(),
+        sort_by(),
+        reverse(),
+        flat_map(),
+        any(),
+        all(),
+        find(),
+        take(),
+        drop(),
+        unique(),
+        group_by(),
+    ]
+}
+
+fn map() -> Builtin {
+    Builtin::new("map", 2, |args, _| match args.as_slice() {
+        [f, Object::Array(arr)] => stopped(
+            arr.iter()
+                .map(|x| call(f, vec![x.clone()]))
+                .collect::<Result<_, _>>()
+                .map(|x| Object::Array(Array(x))),
+        ),
+        _ => Err("invalid arguments for map".to_string()),
+    })
+}
+
+fn filter() -> Builtin {
+    Builtin::new("filter", 2, |args, _| match args.as_slice() {
+        [f, Object::Array(arr)] => stopped((|| {
+            let mut filtered = Vec::new();
+            for x in arr.iter() {
+                if predicate(f, x)? {
+                    filtered.push(x.clone());
+                }
+            }
+            Ok(Object::Array(Array(filtered)))
+        })()),
+        _ => Err("invalid arguments for filter".to_string()),
+    })
+}
+
+fn reduce() -> Builtin {
+    Builtin::new("reduce", 3, |args, _| match args.as_slice() {
+        [f, init, Object::Array(arr)] => stopped(fold_left(f, init, arr)),
+        _ => Err("invalid arguments for reduce".to_string()),
+    })
+}
+
+fn fold() -> Builtin {
+    Builtin::new("fold", 3, |args, _| match args.as_slice() {
+        [f, init, Object::Array(arr)] => stopped(fold_left(f, init, arr)),
+        _ => Err("invalid arguments for fold".to_string()),
+    })
+}
+
+fn range() -> Builtin {
+    Builtin::new("range", 2, |args, _| match args.as_slice() {
+        [Object::Integer(a), Object::Integer(b)] => match b.checked_sub(*a) {
+            Some(n) if n < MAX_LEN as i64 => Ok(Object::Array(Array(
+                (*a..=*b).map(Object::Integer).collect(),
+            ))),
+            _ => Err(format!(
+                "range would build an array longer than {} elements",
+                MAX_LEN
+            )),
+        },
+        _ => Err("invalid arguments for range".to_string()),
+    })
+}
+
+fn zip() -> Builtin {
+    Builtin::new("zip", 2, |args, _| match args.as_slice() {
+        [Object::Array(l), Object::Array(r)] => Ok(Object::Array(Array(
+            l.iter()
+                .zip(r.iter())
+                .map(|(l, r)| Object::Array(Array(vec![l.clone(), r.clone()])))
+                .collect(),
+        ))),
+        _ => Err("invalid arguments for zip".to_string()),
+    })
+}
+
+fn enumerate() -> Builtin {
+    Builtin::new("enumerate", 1, |args, _| match args.as_slice() {
+        [Object::Array(arr)] => Ok(Object::Array(Array(
+            arr.iter()
+                .enumerate()
+                .map(|(i, x)| Object::Array(Array(vec![Object::Integer(i as i64), x.clone()])))
+                .collect(),
+        ))),
+        _ => Err("invalid arguments for enumerate".to_string()),
+    })
+}
+
+fn sort() -> Builtin {
+    Builtin::new("sort", 1, |args, _| match args.as_slice() {
+        [Object::Array(arr)] => {
+            let keyed = arr.iter().map(|x| (x.clone(), x.clone())).collect();
+            sort_keyed(keyed)
+        }
+        _ => Err("invalid arguments for sort".to_string()),
+    })
+}
+
+fn sort_by() -> Builtin {
+    Builtin::new("sort_by", 2, |args, _| match args.as_slice() {
+        [f, Object::Array(arr)] => {
+            let keyed = arr
+                .iter()
+                .map(|x| Ok((call(f, vec![x.clone()])?, x.clone())))
+                .collect::<Result<_, Stop>>();
+            match keyed {
+                Ok(keyed) => sort_keyed(keyed),
+                Err(stop) => stopped(Err(stop)),
+            }
+        }
+        _ => Err("invalid arguments for sort_by".to_string()),
+    })
+}
+
+fn reverse() -> Builtin {
+    Builtin::new("reverse", 1, |args, _| match args.as_slice() {
+        [Object::Array(arr)] => Ok(Object::Array(Array(arr.iter().rev().cloned().collect()))),
+        [Object::String(s)] => Ok(Object::String(s.chars().rev().collect())),
+        _ => Err("invalid arguments for reverse".to_string()),
+    })
+}
+
+fn flat_map() -> Builtin {
+    Builtin::new("flat_map", 2, |args, _| match args.as_slice() {
+        [f, Object::Array(arr)] => stopped((|| {
+            let mut flattened = Vec::new();
+            for x in arr.iter() {
+                match call(f, vec![x.clone()])? {
+                    Object::Array(Array(xs)) => flattened.extend(xs),
+                    o => return Err(format!("{} is not an array", o).into()),
+                }
+            }
+            Ok(Object::Array(Array(flattened)))
+        })()),
+        _ => Err("invalid arguments for flat_map".to_string()),
+    })
+}
+
+fn any() -> Builtin {
+    Builtin::new("any", 2, |args, _| match args.as_slice() {
+        [f, Object::Array(arr)] => stopped((|| {
+            for x in arr.iter() {
+                if predicate(f, x)? {
+                    return Ok(Object::Boolean(true));
+                }
+            }
+            Ok(Object::Boolean(false))
+        })()),
+        _ => Err("invalid arguments for any".to_string()),
+    })
+}
+
+fn all() -> Builtin {
+    Builtin::new("all", 2, |args, _| match args.as_slice() {
+        [f, Object::Array(arr)] => stopped((|| {
+            for x in arr.iter() {
+                if !predicate(f, x)? {
+                    return Ok(Object::Boolean(false));
+                }
+            }
+            Ok(Object::Boolean(true))
+        })()),
+        _ => Err("invalid arguments for all".to_string()),
+    })
+}
+
+fn find() -> Builtin {
+    Builtin::new("find", 2, |args, _| match args.as_slice() {
+        [Object::String(sub), Object::String(s)] => Ok(index_of(sub, s)),
+        [f, Object::Array(arr)] => stopped((|| {
+            for x in arr.iter() {
+                if predicate(f, x)? {
+                    return Ok(x.clone());
+                }
+            }
+            Ok(Object::Null)
+        })()),
+        _ => Err("invalid arguments for find".to_string()),
+    })
+}
+
+fn take() -> Builtin {
+    Builtin::new("take", 2, |args, _| match args.as_slice() {
+        [Object::Integer(n), Object::Array(arr)] if *n >= 0 => Ok(Object::Array(Array(
+            arr.iter().take(*n as usize).cloned().collect(),
+        ))),
+        _ => Err("invalid arguments for take".to_string()),
+    })
+}
+
+fn drop() -> Builtin {
+    Builtin::new("drop", 2, |args, _| match args.as_slice() {
+        [Object::Integer(n), Object::Array(arr)] if *n >= 0 => Ok(Object::Array(Array(
+            arr.iter().skip(*n as usize).cloned().collect(),
+        ))),
+        _ => Err("invalid arguments for drop".to_string()),
+    })
+}
+
+fn unique() -> Builtin {
+    Builtin::new("unique", 1, |args, _| match args.as_slice() {
+        [Object::Array(arr)] => {
+            let mut unique: Vec<Object> = Vec::new();
+            for x in arr.iter() {
+                if !unique.contains(x) {
+                    unique.push(x.clone());
+                }
+            }
+            Ok(Object::Array(Array(unique)))
+        }
+        _ => Err("invalid arguments for unique".to_string()),
+    })
+}
+
+fn group_by() -> Builtin {
+    Builtin::new("group_by", 2, |args, _| match args.as_slice() {
+        [f, Object::Array(arr)] => stopped((|| {
+            let mut groups = IndexMap::new();
+            for x in arr.iter() {
+                let key = call(f, vec![x.clone()])?.hash_key()?;
+                match groups
+                    .entry(key)
+                    .or_insert_with(|| Object::Array(Array(Vec::new())))
+                {
+                    Object::Array(Array(group)) => group.push(x.clone()),
+                    _ => unreachable!(),
+                }
+            }
+            Ok(Object::Hash(Hash(groups)))
+        })()),
+        _ => Err("invalid arguments for group_by".to_string()),
+    })
+}
+
+/// Why a callback ended the builtin that called it.
+enum Stop {
+    Error(String),
+    Exit(i32),
+}
+
+impl From<String> for Stop {
+    fn from(e: String) -> Self {
+        Stop::Error(e)
+    }
+}
+
+fn call(f: &Object, args: Vec<Object>) -> Result<Object, Stop> {
+    match apply_all(f.clone(), args) {
+        Object::Error(e) => Err(Stop::Error(e)),
+        Object::Exit(code) => Err(Stop::Exit(code)),
+        o => Ok(o),
+    }
+}
+
+/// An exit is the result of the builtin, which the evaluator passes up.
+fn stopped(result: Result<Object, Stop>) -> Result<Object, String> {
+    match result {
+        Ok(o) => Ok(o),
+        Err(Stop::Error(e)) => Err(e),
+        Err(Stop::Exit(code)) => Ok(Object::Exit(code)),
+    }
+}
+
+fn predicate(f: &Object, x: &Object) -> Result<bool, Stop> {
+    Ok(call(f, vec![x.clone()])?.boolean()?)
+}
+
+fn fold_left(f: &Object, init: &Object, arr: &[Object]) -> Result<Object, Stop> {
+    arr.iter()
+        .try_fold(init.clone(), |acc, x| call(f, vec![acc, x.clone()]))
+}
+
+fn compare(l: &Object, r: &Object) -> Result<Ordering, String> {
+    match (l, r) {
+        (Object::Integer(l), Object::Integer(r)) => Ok(l.cmp(r)),
+        (Object::Float(l), Object::Float(r)) => l
+            .partial_cmp(r)
+            .ok_or_else(|| format!("cannot compare {} and {}", l, r)),
+        (Object::String(l), Object::String(r)) => Ok(l.cmp(r)),
+        (Object::Boolean(l), Object::Boolean(r)) => Ok(l.cmp(r)),
+        (l, r) => Err(format!("cannot compare {} and {}", l, r)),
+    }
+}
+
+fn sort_keyed(mut keyed: Vec<(Object, Object)>) -> Result<Object, String> {
+    let mut error = None;
+    keyed.sort_by(|(l, _), (r, _)| match compare(l, r) {
+        Ok(ordering) => ordering,
+        Err(e) => {
+            error.get_or_insert(e);
+            Ordering::Equal
+        }
+    });
+    match error {
+        Some(e) => Err(e),
+        None => Ok(Object::Array(Array(
+            keyed.into_iter().map(|(_, x)| x).collect(),
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::object::*;
+    use crate::test_util::eval;
+
+    #[test]
+    fn map_filter_reduce_test() {
+        assert_eq!(eval("map(|x| x * 2, [1, 2, 3])"), eval("[2, 4, 6]"));
+        assert_eq!(eval("map(len)([\"a\", \"bc\"])"), eval("[1, 2]"));
+        assert_eq!(
+            eval("filter(|x| x % 2 == 0, range(1, 6))"),
+            eval("[2, 4, 6]")
+        );
+        assert_eq!(eval("reduce(|a, b| a + b, 0, range(1, 100))"), eval("5050"));
+        assert_eq!(
+            eval("fold(|a, b| b + a, \"\", [\"a\", \"b\"])"),
+            eval("\"ba\"")
+        );
+        assert_eq!(eval("flat_map(|x| [x, x], [1, 2])"), eval("[1, 1, 2, 2]"));
+    }
+
+    #[test]
+    fn deep_recursion_test() {
+        assert_eq!(
+            eval("len(map(|x| x + 1, range(1, 10000)))"),
+            Object::Integer(10000)
+        );
+    }
+
+    #[test]
+    fn reorder_test() {
+        assert_eq!(eval("sort([3, 1, 2])"), eval("[1, 2, 3]"));
+        assert_eq!(
+            eval("sort_by(len, [\"ccc\", \"a\", \"bb\"])"),
+            eval("[\"a\", \"bb\", \"ccc\"]")
+        );
+        assert_eq!(eval("reverse([1, 2, 3])"), eval("[3, 2, 1]"));
+        assert_eq!(eval("reverse(\"蟹かに\")"), eval("\"にか蟹\""));
+        assert_eq!(eval("unique([1, 2, 1, 3, 2])"), eval("[1, 2, 3]"));
+        assert!(matches!(eval("sort([1, \"a\"])"), Object::Error(_)));
+    }
+
+    #[test]
+    fn query_test() {
+        assert_eq!(eval("any(|x| x > 2, [1, 2, 3])"), Object::Boolean(true));
+        assert_eq!(eval("all(|x| x > 2, [1, 2, 3])"), Object::Boolean(false));
+        assert_eq!(eval("find(|x| x > 1, [1, 2, 3])"), Object::Integer(2));
+        assert_eq!(eval("find(|x| x > 3, [1, 2, 3])"), Object::Null);
+        assert_eq!(eval("take(2, [1, 2, 3])"), eval("[1, 2]"));
+        assert_eq!(eval("drop(2, [1, 2, 3])"), eval("[3]"));
+        assert_eq!(eval("range(3, 1)"), eval("[]"));
+        let too_long =
+            Object::Error("range would build an array longer than 16777216 elements".to_string());
+        assert_eq!(eval("range(0, 9223372036854775807)"), too_long);
+        assert_eq!(eval("take(2, range(0, 1000000000000))"), too_long);
+        assert_eq!(eval("range(-9223372036854775807, 1)"), too_long);
+    }
+
+    #[test]
+    fn combine_test() {
+        assert_eq!(
+            eval("zip([1, 2, 3], [\"a\", \"b\"])"),
+            eval("[[1, \"a\"], [2, \"b\"]]")
+        );
+        assert_eq!(
+            eval("enumerate([\"a\", \"b\"])"),
+            eval("[[0, \"a\"], [1, \"b\"]]")
+        );
+        assert_eq!(
+            eval("group_by(|x| x % 2 == 0, [1, 2, 3, 4])"),
+            eval("{false: [1, 3], true: [2, 4]}")
+        );
+    }
+
+    #[test]
+    fn callback_error_test() {
+        assert_eq!(
+            eval("map(|x| x + \"a\", [1])"),
+            Object::Error("unsupported operation `+`: Integer(1) and String(\"a\")".to_string())
+        );
+    }
+}
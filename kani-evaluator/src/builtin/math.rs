@@ -0,0 +1,115 @@
+use crate::object::*;
+
+pub fn builtins() -> Vec<Builtin> {
+    vec![abs(), floor(), ceil(), round(), sqrt(), clamp()]
+}
+
+fn abs() -> Builtin {
+    Builtin::new("abs", 1, |args, _| match args.as_slice() {
+        [Object::Integer(i)] => match i.checked_abs() {
+            Some(i) => Ok(Object::Integer(i)),
+            None => Err(format!("abs of {} is out of range", i)),
+        },
+        [Object::Float(f)] => Ok(Object::Float(f.abs())),
+        _ => Err("invalid arguments for abs".to_string()),
+    })
+}
+
+fn floor() -> Builtin {
+    Builtin::new("floor", 1, |args, _| match args.as_slice() {
+        [Object::Integer(i)] => Ok(Object::Integer(*i)),
+        [Object::Float(f)] => integer("floor", f.floor()),
+        _ => Err("invalid arguments for floor".to_string()),
+    })
+}
+
+fn ceil() -> Builtin {
+    Builtin::new("ceil", 1, |args, _| match args.as_slice() {
+        [Object::Integer(i)] => Ok(Object::Integer(*i)),
+        [Object::Float(f)] => integer("ceil", f.ceil()),
+        _ => Err("invalid arguments for ceil".to_string()),
+    })
+}
+
+fn round() -> Builtin {
+    Builtin::new("round", 1, |args, _| match args.as_slice() {
+        [Object::Integer(i)] => Ok(Object::Integer(*i)),
+        [Object::Float(f)] => integer("round", f.round()),
+        _ => Err("invalid arguments for round".to_string()),
+    })
+}
+
+fn sqrt() -> Builtin {
+    Builtin::new("sqrt", 1, |args, _| match args.as_slice() {
+        [Object::Integer(i)] if *i >= 0 => Ok(Object::Float((*i as f64).sqrt())),
+        [Object::Float(f)] if *f >= 0.0 => Ok(Object::Float(f.sqrt())),
+        _ => Err("invalid arguments for sqrt".to_string()),
+    })
+}
+
+fn clamp() -> Builtin {
+    Builtin::new("clamp", 3, |args, _| match args.as_slice() {
+        [Object::Integer(lo), Object::Integer(hi), Object::Integer(x)] if lo <= hi => {
+            Ok(Object::Integer(*x.max(lo).min(hi)))
+        }
+        [Object::Float(lo), Object::Float(hi), Object::Float(x)] if lo <= hi => {
+            Ok(Object::Float(x.max(*lo).min(*hi)))
+        }
+        _ => Err("invalid arguments for clamp".to_string()),
+    })
+}
+
+/// Rejects NaN, infinities and floats beyond the integer range, which a cast
+/// would silently saturate.
+fn integer(name: &str, f: f64) -> Result<Object, String> {
+    if f >= i64::MIN as f64 && f < i64::MAX as f64 {
+        Ok(Object::Integer(f as i64))
+    } else {
+        Err(format!("{} of {} is out of range", name, f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::object::*;
+    use crate::test_util::eval;
+
+    #[test]
+    fn rounding_test() {
+        assert_eq!(eval("abs(-3)"), Object::Integer(3));
+        assert_eq!(eval("abs(-1.5)"), Object::Float(1.5));
+        assert_eq!(eval("floor(1.7)"), Object::Integer(1));
+        assert_eq!(eval("ceil(1.2)"), Object::Integer(2));
+        assert_eq!(eval("round(2.5)"), Object::Integer(3));
+        assert_eq!(eval("round(4)"), Object::Integer(4));
+        assert_eq!(
+            eval("abs(0 - 9223372036854775807 - 1)"),
+            Object::Error("abs of -9223372036854775808 is out of range".to_string())
+        );
+        assert_eq!(
+            eval("floor(0.0 / 0.0)"),
+            Object::Error("floor of NaN is out of range".to_string())
+        );
+        assert_eq!(
+            eval("ceil(1.0 / 0.0)"),
+            Object::Error("ceil of inf is out of range".to_string())
+        );
+        assert_eq!(
+            eval("round(10000000000000000000000.0)"),
+            Object::Error("round of 10000000000000000000000 is out of range".to_string())
+        );
+    }
+
+    #[test]
+    fn sqrt_clamp_test() {
+        assert_eq!(eval("sqrt(9)"), Object::Float(3.0));
+        assert_eq!(
+            eval("sqrt(-1.0)"),
+            Object::Error("invalid arguments for sqrt".to_string())
+        );
+        assert_eq!(eval("clamp(0, 10, 12)"), Object::Integer(10));
+        assert_eq!(eval("clamp(0, 10, -2)"), Object::Integer(0));
+        assert_eq!(eval("clamp(0.0, 1.0, 1.5)"), Object::Float(1.0));
+        assert_eq!(eval("[1, 20].map(clamp(0, 10))"), eval("[1, 10]"));
+    }
+}
@@ -0,0 +1,89 @@
+mod hash;
+mod io;
+mod json;
+mod list;
+mod math;
+mod string;
+
+pub(crate) use io::allowed;
+
+use super::object::*;
+
+/// A group of builtins that can be installed into an evaluator on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuiltinModule {
+    Core,
+    Io,
+    String,
+    List,
+    Hash,
+    Math,
+    Json,
+}
+
+impl BuiltinModule {
+    pub const ALL: [Self; 7] = [
+        Self::Core,
+        Self::Io,
+        Self::String,
+        Self::List,
+        Self::Hash,
+        Self::Math,
+        Self::Json,
+    ];
+
+    pub fn builtins(self) -> Vec<Builtin> {
+        match self {
+            Self::Core => vec![len(), head(), tail(), memo()],
+            Self::Io => io::builtins(),
+            Self::String => string::builtins(),
+            Self::List => list::builtins(),
+            Self::Hash => hash::builtins(),
+            Self::Math => math::builtins(),
+            Self::Json => json::builtins(),
+        }
+    }
+}
+
+pub fn builtins() -> Vec<Builtin> {
+    BuiltinModule::ALL
+        .iter()
+        .flat_map(|module| module.builtins())
+        .collect()
+}
+
+fn len() -> Builtin {
+    Builtin::new("len", 1, |args, _| match args.as_slice() {
+        [Object::String(s)] => Ok(Object::Integer(s.chars().count() as i64)),
+        [Object::Array(arr)] => Ok(Object::Integer(arr.len() as i64)),
+        [Object::Hash(hash)] => Ok(Object::Integer(hash.len() as i64)),
+        _ => Err("invalid arguments for len".to_string()),
+    })
+}
+
+fn head() -> Builtin {
+    Builtin::new("head", 1, |args, _| match args.as_slice() {
+        [Object::Array(arr)] => match arr.first() {
+            Some(x) => Ok(x.clone()),
+            None => Err("empty array".to_string()),
+        },
+        _ => Err("invalid arguments for head".to_string()),
+    })
+}
+
+fn tail() -> Builtin {
+    Builtin::new("tail", 1, |args, _| match args.as_slice() {
+        [Object::Array(arr)] => match arr.len() {
+            0 => Err("empty array".to_string()),
+            _ => Ok(Object::Array(Array(arr[1..].into()))),
+        },
+        _ => Err("invalid arguments for tail".to_string()),
+    })
+}
+
+fn memo() -> Builtin {
+    Builtin::new("memo", 1, |args, _| match args.as_slice() {
+        [f] if f.is_callable() => Ok(Object::Memo(Memo::new(f.clone()))),
+        _ => Err("invalid arguments for memo".to_string()),
+    })
+}
@@ -0,0 +1,268 @@
+use crate::object::*;
+
+/// The longest string, in bytes, that `repeat` and padding may build.
+const MAX_LEN: usize = 1 << 24;
+
+pub fn builtins() -> Vec<Builtin> {
+    vec![
+        split(),
+        join(),
+        trim(),
+        upper(),
+        lower(),
+        replace(),
+        contains(),
+        starts_with(),
+        ends_with(),
+        chars(),
+        repeat(),
+        pad_left(),
+        pad_right(),
+        char_len(),
+        byte_len(),
+    ]
+}
+
+fn split() -> Builtin {
+    Builtin::new("split", 2, |args, _| match args.as_slice() {
+        [Object::String(sep), Object::String(s)] if sep.is_empty() => Ok(to_chars(s)),
+        [Object::String(sep), Object::String(s)] => Ok(Object::Array(Array(
+            s.split(sep.as_str())
+                .map(|x| Object::String(x.to_string()))
+                .collect(),
+        ))),
+        _ => Err("invalid arguments for split".to_string()),
+    })
+}
+
+fn join() -> Builtin {
+    Builtin::new("join", 2, |args, _| match args.as_slice() {
+        [Object::String(sep), Object::Array(arr)] => Ok(Object::String(
+            arr.iter()
+                .map(|o| format!("{}", o))
+                .collect::<Vec<_>>()
+                .join(sep),
+        )),
+        _ => Err("invalid arguments for join".to_string()),
+    })
+}
+
+fn trim() -> Builtin {
+    Builtin::new("trim", 1, |args, _| match args.as_slice() {
+        [Object::String(s)] => Ok(Object::String(s.trim().to_string())),
+        _ => Err("invalid arguments for trim".to_string()),
+    })
+}
+
+fn upper() -> Builtin {
+    Builtin::new("upper", 1, |args, _| match args.as_slice() {
+        [Object::String(s)] => Ok(Object::String(s.to_uppercase())),
+        _ => Err("invalid arguments for upper".to_string()),
+    })
+}
+
+fn lower() -> Builtin {
+    Builtin::new("lower", 1, |args, _| match args.as_slice() {
+        [Object::String(s)] => Ok(Object::String(s.to_lowercase())),
+        _ => Err("invalid arguments for lower".to_string()),
+    })
+}
+
+fn replace() -> Builtin {
+    Builtin::new("replace", 3, |args, _| match args.as_slice() {
+        [Object::String(from), Object::String(to), Object::String(s)] => {
+            Ok(Object::String(s.replace(from.as_str(), to)))
+        }
+        _ => Err("invalid arguments for replace".to_string()),
+    })
+}
+
+fn contains() -> Builtin {
+    Builtin::new("contains", 2, |args, _| match args.as_slice() {
+        [Object::String(sub), Object::String(s)] => Ok(Object::Boolean(s.contains(sub.as_str()))),
+        _ => Err("invalid arguments for contains".to_string()),
+    })
+}
+
+fn starts_with() -> Builtin {
+    Builtin::new("starts_with", 2, |args, _| match args.as_slice() {
+        [Object::String(prefix), Object::String(s)] => {
+            Ok(Object::Boolean(s.starts_with(prefix.as_str())))
+        }
+        _ => Err("invalid arguments for starts_with".to_string()),
+    })
+}
+
+fn ends_with() -> Builtin {
+    Builtin::new("ends_with", 2, |args, _| match args.as_slice() {
+        [Object::String(suffix), Object::String(s)] => {
+            Ok(Object::Boolean(s.ends_with(suffix.as_str())))
+        }
+        _ => Err("invalid arguments for ends_with".to_string()),
+    })
+}
+
+fn chars() -> Builtin {
+    Builtin::new("chars", 1, |args, _| match args.as_slice() {
+        [Object::String(s)] => Ok(to_chars(s)),
+        _ => Err("invalid arguments for chars".to_string()),
+    })
+}
+
+fn repeat() -> Builtin {
+    Builtin::new("repeat", 2, |args, _| match args.as_slice() {
+        [Object::Integer(n), Object::String(s)] if *n >= 0 => {
+            match s.len().checked_mul(*n as usize) {
+                Some(len) if len <= MAX_LEN => Ok(Object::String(s.repeat(*n as usize))),
+                _ => Err(too_long("repeat")),
+            }
+        }
+        _ => Err("invalid arguments for repeat".to_string()),
+    })
+}
+
+fn pad_left() -> Builtin {
+    Builtin::new("pad_left", 3, |args, _| match args.as_slice() {
+        [Object::Integer(width), Object::String(fill), Object::String(s)] => {
+            let padding = padding("pad_left", *width, fill, s)?;
+            Ok(Object::String(padding + s))
+        }
+        _ => Err("invalid arguments for pad_left".to_string()),
+    })
+}
+
+fn pad_right() -> Builtin {
+    Builtin::new("pad_right", 3, |args, _| match args.as_slice() {
+        [Object::Integer(width), Object::String(fill), Object::String(s)] => {
+            let padding = padding("pad_right", *width, fill, s)?;
+            Ok(Object::String(s.clone() + &padding))
+        }
+        _ => Err("invalid arguments for pad_right".to_string()),
+    })
+}
+
+fn char_len() -> Builtin {
+    Builtin::new("char_len", 1, |args, _| match args.as_slice() {
+        [Object::String(s)] => Ok(Object::Integer(s.chars().count() as i64)),
+        _ => Err("invalid arguments for char_len".to_string()),
+    })
+}
+
+fn byte_len() -> Builtin {
+    Builtin::new("byte_len", 1, |args, _| match args.as_slice() {
+        [Object::String(s)] => Ok(Object::Integer(s.len() as i64)),
+        _ => Err("invalid arguments for byte_len".to_string()),
+    })
+}
+
+pub fn index_of(sub: &str, s: &str) -> Object {
+    match s.find(sub) {
+        Some(i) => Object::Integer(s[..i].chars().count() as i64),
+        None => Object::Null,
+    }
+}
+
+fn to_chars(s: &str) -> Object {
+    Object::Array(Array(
+        s.chars().map(|c| Object::String(c.to_string())).collect(),
+    ))
+}
+
+fn too_long(name: &str) -> String {
+    format!(
+        "{} would build a string longer than {} bytes",
+        name, MAX_LEN
+    )
+}
+
+fn padding(name: &str, width: i64, fill: &str, s: &str) -> Result<String, String> {
+    let mut fill = fill.chars();
+    match (fill.next(), fill.next()) {
+        (Some(c), None) => {
+            let count = (width - s.chars().count() as i64).max(0) as usize;
+            match count.checked_mul(c.len_utf8()) {
+                Some(len) if len + s.len() <= MAX_LEN => Ok(c.to_string().repeat(count)),
+                _ => Err(too_long(name)),
+            }
+        }
+        _ => Err("padding must be a single character".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::object::*;
+    use crate::test_util::eval;
+
+    fn strings(xs: &[&str]) -> Object {
+        Object::Array(Array(
+            xs.iter().map(|x| Object::String(x.to_string())).collect(),
+        ))
+    }
+
+    #[test]
+    fn split_join_test() {
+        assert_eq!(eval("split(\",\", \"a,b,c\")"), strings(&["a", "b", "c"]));
+        assert_eq!(eval("split(\"\", \"日本\")"), strings(&["日", "本"]));
+        assert_eq!(eval("join(\"-\", [1, \"b\", true])"), eval("\"1-b-true\""));
+        assert_eq!(
+            eval("csv = split(\",\"); csv(\"x,y\")"),
+            strings(&["x", "y"])
+        );
+    }
+
+    #[test]
+    fn transform_test() {
+        assert_eq!(eval("trim(\"  kani \")"), eval("\"kani\""));
+        assert_eq!(eval("upper(\"kani\")"), eval("\"KANI\""));
+        assert_eq!(eval("lower(\"KANI\")"), eval("\"kani\""));
+        assert_eq!(
+            eval("replace(\"a\", \"o\", \"banana\")"),
+            eval("\"bonono\"")
+        );
+        assert_eq!(eval("repeat(3, \"ab\")"), eval("\"ababab\""));
+        assert_eq!(eval("pad_left(5, \"0\", \"42\")"), eval("\"00042\""));
+        assert_eq!(eval("pad_right(4, \"*\", \"蟹\")"), eval("\"蟹***\""));
+        assert_eq!(eval("chars(\"蟹a\")"), strings(&["蟹", "a"]));
+    }
+
+    #[test]
+    fn limit_test() {
+        assert_eq!(
+            eval("repeat(1_000_000_000_000, \"x\")"),
+            Object::Error("repeat would build a string longer than 16777216 bytes".to_string())
+        );
+        assert_eq!(
+            eval("pad_left(9_223_372_036_854_775_807, \"蟹\", \"\")"),
+            Object::Error("pad_left would build a string longer than 16777216 bytes".to_string())
+        );
+        assert_eq!(
+            eval("len(repeat(1024, repeat(1024, \"ab\")))"),
+            Object::Integer(2 << 20)
+        );
+    }
+
+    #[test]
+    fn query_test() {
+        assert_eq!(eval("contains(\"an\", \"banana\")"), Object::Boolean(true));
+        assert_eq!(
+            eval("starts_with(\"ba\", \"banana\")"),
+            Object::Boolean(true)
+        );
+        assert_eq!(
+            eval("ends_with(\"ba\", \"banana\")"),
+            Object::Boolean(false)
+        );
+        assert_eq!(eval("find(\"本\", \"日本語\")"), Object::Integer(1));
+        assert_eq!(eval("find(\"x\", \"日本語\")"), Object::Null);
+        assert_eq!(eval("char_len(\"日本語\")"), Object::Integer(3));
+        assert_eq!(eval("len(\"日本語\")"), Object::Integer(3));
+        assert_eq!(eval("byte_len(\"日本語\")"), Object::Integer(9));
+    }
+
+    #[test]
+    fn index_test() {
+        assert_eq!(eval("\"日本語\"[1]"), eval("\"本\""));
+        assert_eq!(eval("\"日本語\"[3]"), Object::Null);
+    }
+}
@@ -0,0 +1,106 @@
+//! State shared by every scope of one evaluator.
+
+use crate::environment::Environment;
+use crate::module::Modules;
+use crate::output::{Sink, Stderr, Stdout};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::path::PathBuf;
+use std::rc::Weak;
+
+pub struct Context {
+    pub limits: Limits,
+    pub capabilities: Capabilities,
+    pub(crate) stdout: RefCell<Box<dyn Sink>>,
+    pub(crate) stderr: RefCell<Box<dyn Sink>>,
+    pub(crate) depth: Cell<usize>,
+    pub(crate) modules: RefCell<Modules>,
+    /// The scope holding the builtins and the prelude, which imported files
+    /// are evaluated in children of.
+    pub(crate) globals: RefCell<Weak<RefCell<Environment>>>,
+}
+
+/// Only the call depth can be bounded for now. Strings built by builtins such
+/// as `repeat` have a fixed cap instead, and there is no step or memory limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Limits {
+    pub max_call_depth: Option<usize>,
+}
+
+/// What the IO builtins may do. Everything is denied by default, which suits
+/// embedding; a standalone interpreter grants [`Capabilities::all`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Capabilities {
+    /// Directories that `read_file` and `list_dir` may access.
+    pub read: Vec<PathBuf>,
+    /// Directories that `write_file` may write into. Leave empty for read-only.
+    pub write: Vec<PathBuf>,
+    pub stdin: bool,
+    pub env: bool,
+    pub exit: bool,
+}
+
+impl Capabilities {
+    pub fn all() -> Self {
+        Self {
+            read: vec![PathBuf::from("/")],
+            write: vec![PathBuf::from("/")],
+            stdin: true,
+            env: true,
+            exit: true,
+        }
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new(
+            Limits::default(),
+            Capabilities::default(),
+            Box::new(Stdout),
+            Box::new(Stderr),
+        )
+    }
+}
+
+impl Context {
+    pub fn new(
+        limits: Limits,
+        capabilities: Capabilities,
+        stdout: Box<dyn Sink>,
+        stderr: Box<dyn Sink>,
+    ) -> Self {
+        Self {
+            limits,
+            capabilities,
+            stdout: RefCell::new(stdout),
+            stderr: RefCell::new(stderr),
+            depth: Cell::default(),
+            modules: RefCell::default(),
+            globals: RefCell::default(),
+        }
+    }
+
+    pub fn print(&self, line: &str) {
+        self.stdout.borrow_mut().write_line(line);
+    }
+
+    pub fn eprint(&self, line: &str) {
+        self.stderr.borrow_mut().write_line(line);
+    }
+}
+
+impl PartialEq for Context {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl fmt::Debug for Context {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("limits", &self.limits)
+            .field("capabilities", &self.capabilities)
+            .finish()
+    }
+}
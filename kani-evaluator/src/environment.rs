@@ -1,44 +1,107 @@
 use super::builtin::builtins;
-use super::object::Object;
+use super::context::Context;
+use super::object::{Builtin, Object};
+use indexmap::IndexMap;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 #[derive(PartialEq, Debug, Clone)]
 pub struct Environment {
-    store: HashMap<String, Object>,
+    store: IndexMap<String, Object>,
+    /// Names bound by `import` rather than assigned here.
+    imported: HashSet<String>,
     parent: Option<Rc<RefCell<Environment>>>,
+    context: Rc<Context>,
+    path: Option<PathBuf>,
 }
 
 impl Default for Environment {
     fn default() -> Self {
-        let mut store = HashMap::new();
-        fill_env_with_builtins(&mut store);
-        Self::new(store, None)
+        Self::with_builtins(builtins(), Rc::default())
     }
 }
 
 impl From<Rc<RefCell<Environment>>> for Environment {
     fn from(environment: Rc<RefCell<Environment>>) -> Self {
-        let mut store = HashMap::new();
-        fill_env_with_builtins(&mut store);
-        Self::new(store, Some(environment))
+        Self::new(IndexMap::new(), Some(environment))
     }
 }
 
-fn fill_env_with_builtins(hashmap: &mut HashMap<String, Object>) {
-    for f in builtins().iter() {
-        hashmap.insert(f.name.clone(), Object::Builtin(f.clone()));
+impl Environment {
+    /// A child environment shares the context of its parent.
+    pub fn new(store: IndexMap<String, Object>, parent: Option<Rc<RefCell<Environment>>>) -> Self {
+        let context = match &parent {
+            Some(env) => env.borrow().context(),
+            None => Rc::default(),
+        };
+        Self {
+            store,
+            imported: HashSet::new(),
+            parent,
+            context,
+            path: None,
+        }
     }
-}
 
-impl Environment {
-    pub fn new(store: HashMap<String, Object>, parent: Option<Rc<RefCell<Environment>>>) -> Self {
-        Self { store, parent }
+    /// Creates a root environment. Builtins live only here, so child scopes
+    /// start empty and look them up through their parents.
+    pub fn with_builtins(builtins: Vec<Builtin>, context: Rc<Context>) -> Self {
+        let store = builtins
+            .into_iter()
+            .map(|mut f| {
+                f.context = Rc::clone(&context);
+                (f.name.clone(), Object::Builtin(f))
+            })
+            .collect();
+        Self {
+            store,
+            imported: HashSet::new(),
+            parent: None,
+            context,
+            path: None,
+        }
+    }
+
+    /// Marks this environment as the top level of the file at `path`.
+    pub fn with_path(mut self, path: PathBuf) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    pub fn context(&self) -> Rc<Context> {
+        Rc::clone(&self.context)
+    }
+
+    /// Returns the path of the file this environment belongs to, if any.
+    pub fn path(&self) -> Option<PathBuf> {
+        match (&self.path, &self.parent) {
+            (Some(path), _) => Some(path.clone()),
+            (None, Some(env)) => env.borrow().path(),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns the bindings assigned directly in this environment, in the
+    /// order they were first made. Imported names are not re-exported.
+    pub fn exports(&self) -> Vec<(String, Object)> {
+        self.store
+            .iter()
+            .filter(|(k, _)| !self.imported.contains(*k))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
     }
 
     pub fn set(&mut self, name: &str, val: Object) {
+        self.imported.remove(name);
+        self.store.insert(name.to_string(), val);
+    }
+
+    /// Binds a name brought in by `import`.
+    pub fn import(&mut self, name: &str, val: Object) {
         self.store.insert(name.to_string(), val);
+        self.imported.insert(name.to_string());
     }
 
     pub fn get(&self, name: &str) -> Option<Object> {
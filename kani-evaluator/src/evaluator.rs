@@ -1,13 +1,15 @@
 use crate::environment::*;
+use crate::module;
 use crate::nom;
 use crate::object::*;
+use indexmap::IndexMap;
 use kani_parser::ast::{
-    self, Argument, Assign, Block, Expression, HashKey, Identifier, If, Index, Infix,
-    InfixOperator, Literal, Postfix, PostfixOperator, Prefix, PrefixOperator,
+    self, Argument, Assign, Block, Expression, Field, Identifier, If, Import, ImportBinding, Index,
+    Infix, InfixOperator, Literal, Method, NamedArgument, Parameter, Postfix, PostfixOperator,
+    Prefix, PrefixOperator, Spread, Template, TemplatePart,
 };
 use kani_parser::program;
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::rc::Rc;
 
 pub fn eval_code<'a>(
@@ -23,7 +25,7 @@ pub fn eval_expressions(expressions: &[Expression], env: &Rc<RefCell<Environment
         1 => eval_expression(expressions[0].clone(), env),
         _ => {
             let object = eval_expression(expressions[0].clone(), env);
-            if object.is_returned() {
+            if object.is_unwinding() {
                 object
             } else {
                 eval_expressions(&expressions[1..], env)
@@ -39,6 +41,7 @@ pub fn eval_expression(expression: Expression, env: &Rc<RefCell<Environment>>) -
         Expression::Return(r) => eval_return(r, env),
         Expression::Identifier(i) => eval_identifier(i, env),
         Expression::Literal(l) => eval_literal(l),
+        Expression::Template(t) => eval_template(t, env),
         Expression::Prefix(p) => eval_prefix(p, env),
         Expression::Postfix(p) => eval_postfix(p, env),
         Expression::Infix(i) => eval_infix(i, env),
@@ -46,6 +49,8 @@ pub fn eval_expression(expression: Expression, env: &Rc<RefCell<Environment>>) -
         Expression::Function(f) => eval_function(f, env),
         Expression::Array(e) => eval_array(e, env),
         Expression::Hash(h) => eval_hash(h, env),
+        Expression::Spread(_) => Object::Error("unexpected spread".to_string()),
+        Expression::Import(i) => eval_import(i, env),
     }
 }
 
@@ -56,16 +61,49 @@ fn eval_block(Block(block): Block, env: &Rc<RefCell<Environment>>) -> Object {
 
 fn eval_assign(
     Assign {
+        decorators,
         identifier: Identifier(name),
         expression,
     }: Assign,
     env: &Rc<RefCell<Environment>>,
 ) -> Object {
-    let object = eval_expression(*expression, env);
+    let mut object = eval_expression(*expression, env);
+    for decorator in decorators.into_iter().rev() {
+        object = apply(eval_expression(decorator, env), Some(object));
+    }
     env.borrow_mut().set(&name, object.clone());
     object
 }
 
+fn eval_import(Import { path, binding }: Import, env: &Rc<RefCell<Environment>>) -> Object {
+    let exports = match module::import(&path, env) {
+        Object::Hash(Hash(exports)) => exports,
+        o => return o,
+    };
+    let mut env = env.borrow_mut();
+    match binding {
+        ImportBinding::All => {
+            for (name, object) in exports {
+                if let Object::String(name) = name {
+                    env.import(&name, object);
+                }
+            }
+        }
+        ImportBinding::Namespace(Identifier(name)) => {
+            env.import(&name, Object::Hash(Hash(exports)))
+        }
+        ImportBinding::Names(names) => {
+            for Identifier(name) in names {
+                match exports.get(&Object::String(name.clone())) {
+                    Some(object) => env.import(&name, object.clone()),
+                    None => return Object::Error(format!("{} is not exported by {}", name, path)),
+                }
+            }
+        }
+    }
+    Object::Null
+}
+
 fn eval_return(ast::Return(expression): ast::Return, env: &Rc<RefCell<Environment>>) -> Object {
     Object::Return(Return(Box::new(eval_expression(*expression, env))))
 }
@@ -86,12 +124,18 @@ fn eval_literal(literal: Literal) -> Object {
     }
 }
 
-fn eval_hash_key(literal: HashKey) -> Object {
-    match literal {
-        HashKey::Int(i) => Object::Integer(i),
-        HashKey::Bool(b) => Object::Boolean(b),
-        HashKey::Str(s) => Object::String(s),
+fn eval_template(Template(parts): Template, env: &Rc<RefCell<Environment>>) -> Object {
+    let mut string = String::new();
+    for part in parts {
+        match part {
+            TemplatePart::Str(s) => string.push_str(&s),
+            TemplatePart::Expression(e) => match eval_expression(e, env) {
+                o if o.is_unwinding() => return o,
+                o => string.push_str(&format!("{}", o)),
+            },
+        }
     }
+    Object::String(string)
 }
 
 fn eval_prefix(
@@ -103,22 +147,24 @@ fn eval_prefix(
 ) -> Object {
     let object = eval_expression(*expression, env);
     match operator {
+        _ if object.is_unwinding() => object,
         PrefixOperator::Not => !object,
         PrefixOperator::Plus => object.positive(),
         PrefixOperator::Minus => -object,
     }
 }
 
-fn eval_postfix(
-    Postfix {
+fn eval_postfix(postfix: Postfix, env: &Rc<RefCell<Environment>>) -> Object {
+    let Postfix {
         operator,
         expression,
-    }: Postfix,
-    env: &Rc<RefCell<Environment>>,
-) -> Object {
+    } = postfix;
     match operator {
-        PostfixOperator::Call(arguments) => eval_call(*expression, arguments, env),
+        PostfixOperator::Call(_) | PostfixOperator::NamedCall(_) | PostfixOperator::Method(_) => {
+            fill_defaults(eval_callee(Postfix::new(operator, *expression).into(), env))
+        }
         PostfixOperator::Index(Index(index)) => eval_index(*expression, *index, env),
+        PostfixOperator::Field(Field(field)) => eval_field(*expression, field, env),
     }
 }
 
@@ -131,8 +177,12 @@ fn eval_infix(
     env: &Rc<RefCell<Environment>>,
 ) -> Object {
     let left = eval_expression(*left, env);
+    if left.is_unwinding() {
+        return left;
+    }
     let right = eval_expression(*right, env);
     match operator {
+        _ if right.is_unwinding() => right,
         InfixOperator::Plus => left + right,
         InfixOperator::Minus => left - right,
         InfixOperator::Multiply => left * right,
@@ -144,6 +194,10 @@ fn eval_infix(
         InfixOperator::GreaterThanEqual => left.greater_than_equal(right),
         InfixOperator::LessThan => left.less_than(right),
         InfixOperator::LessThanEqual => left.less_than_equal(right),
+        InfixOperator::Pipeline => apply(right, Some(left)),
+        InfixOperator::ComposeRight => left.compose(right),
+        InfixOperator::ComposeLeft => right.compose(left),
+        InfixOperator::Apply => apply(left, Some(right)),
     }
 }
 
@@ -156,6 +210,9 @@ fn eval_if(
     env: &Rc<RefCell<Environment>>,
 ) -> Object {
     let object = eval_expression(*cond, env);
+    if object.is_unwinding() {
+        return object;
+    }
     match object.boolean() {
         Ok(b) => {
             if b {
@@ -172,75 +229,674 @@ fn eval_if(
 }
 
 fn eval_function(
-    ast::Function { param, body }: ast::Function,
+    ast::Function {
+        param,
+        body,
+        curried,
+    }: ast::Function,
     env: &Rc<RefCell<Environment>>,
 ) -> Object {
-    Object::Function(Function::new(param, *body, Rc::clone(env)))
+    Object::Function(Function {
+        partial: curried,
+        ..Function::new(param, *body, Rc::clone(env))
+    })
 }
 
-fn eval_call(expression: Expression, arg: Argument, env: &Rc<RefCell<Environment>>) -> Object {
-    let object = eval_expression(expression, env);
-    match object.function() {
-        Ok(FunctionType::Function(f)) => eval_function_call(arg, f, env),
-        Ok(FunctionType::Builtin(f)) => eval_builtin_call(arg, f, env),
-        Err(e) => Object::Error(e),
+/// Evaluates the callee of a call without filling its defaults, so that
+/// `f(1, 2)` and `f(1)(2)` only fill the defaults left after the last call.
+fn eval_callee(expression: Expression, env: &Rc<RefCell<Environment>>) -> Object {
+    match expression {
+        Expression::Postfix(Postfix {
+            operator: PostfixOperator::Call(arg),
+            expression,
+        }) => call_argument(eval_callee(*expression, env), arg, env),
+        Expression::Postfix(Postfix {
+            operator: PostfixOperator::NamedCall(named),
+            expression,
+        }) => call_named_argument(eval_callee(*expression, env), named, env),
+        Expression::Postfix(Postfix {
+            operator: PostfixOperator::Method(method),
+            expression,
+        }) => eval_method(*expression, method, env),
+        expression => eval_expression(expression, env),
     }
 }
 
-fn eval_function_call(arg: Argument, function: Function, env: &Rc<RefCell<Environment>>) -> Object {
-    let mut child = Environment::from(Rc::clone(&function.env));
-    if let Some(arg) = &*arg {
-        let arg = eval_expression(*arg.clone(), env);
-        if let Some(Identifier(name)) = function.param {
-            child.set(&name, arg);
+/// `receiver.name(args)` calls the field `name` when the receiver is a hash
+/// that has one, such as an imported namespace, and `name(args, receiver)`
+/// otherwise.
+fn eval_method(
+    receiver: Expression,
+    Method {
+        name: Identifier(name),
+        arguments,
+    }: Method,
+    env: &Rc<RefCell<Environment>>,
+) -> Object {
+    let receiver = eval_expression(receiver, env);
+    let field = match &receiver {
+        Object::Hash(Hash(hash)) => hash.get(&Object::String(name.clone())).cloned(),
+        Object::Error(e) => return Object::Error(e.clone()),
+        _ => None,
+    };
+    match field {
+        Some(function) if arguments.is_empty() => apply_one(function, None),
+        Some(function) => call_arguments(function, arguments, env),
+        None => {
+            let function = eval_identifier(Identifier(name), env);
+            apply_one(call_arguments(function, arguments, env), Some(receiver))
         }
     }
-    let object = eval_expression(function.body, &Rc::new(RefCell::new(child)));
-    object.returned()
 }
 
-fn eval_builtin_call(
-    arg: Argument,
-    Builtin { function, .. }: Builtin,
+fn call_arguments(
+    object: Object,
+    arguments: Vec<PostfixOperator>,
+    env: &Rc<RefCell<Environment>>,
+) -> Object {
+    arguments
+        .into_iter()
+        .fold(object, |object, operator| match operator {
+            PostfixOperator::Call(arg) => call_argument(object, arg, env),
+            PostfixOperator::NamedCall(named) => call_named_argument(object, named, env),
+            _ => Object::Error("unexpected method argument".to_string()),
+        })
+}
+
+fn call_argument(
+    object: Object,
+    Argument(arg): Argument,
+    env: &Rc<RefCell<Environment>>,
+) -> Object {
+    match arg.map(|a| *a) {
+        Some(Expression::Spread(Spread(spread))) => match eval_spread(*spread, env) {
+            Object::Array(Array(args)) => args
+                .into_iter()
+                .fold(object, |object, arg| apply_one(object, Some(arg))),
+            o => o,
+        },
+        arg => apply_one(object, arg.map(|a| eval_expression(a, env))),
+    }
+}
+
+/// Evaluates to the spread array, or to the error or exit that stops it.
+fn eval_spread(expression: Expression, env: &Rc<RefCell<Environment>>) -> Object {
+    match eval_expression(expression, env) {
+        o @ Object::Array(_) => o,
+        o if o.is_unwinding() => o,
+        o => Object::Error(format!("cannot spread {}", o)),
+    }
+}
+
+fn call_named_argument(
+    object: Object,
+    NamedArgument {
+        name: Identifier(name),
+        expression: arg,
+    }: NamedArgument,
     env: &Rc<RefCell<Environment>>,
 ) -> Object {
-    let arg = arg.as_ref().map(|a| eval_expression(*a.clone(), env));
-    match function(arg) {
+    match object {
+        Object::Function(function) => match eval_expression(*arg, env) {
+            arg if arg.is_unwinding() => arg,
+            arg => bind_named(Object::Function(function), vec![(name, arg)]),
+        },
+        Object::Error(e) => Object::Error(e),
+        o => Object::Error(format!("{} does not accept named arguments", o)),
+    }
+}
+
+pub fn apply(object: Object, arg: Option<Object>) -> Object {
+    fill_defaults(apply_one(object, arg))
+}
+
+pub fn apply_all(object: Object, args: Vec<Object>) -> Object {
+    let object = args
+        .into_iter()
+        .fold(object, |object, arg| apply_one(object, Some(arg)));
+    fill_defaults(object)
+}
+
+/// An argument that is an error or an exit is passed up instead of being
+/// bound, so that it ends the call.
+fn apply_one(object: Object, arg: Option<Object>) -> Object {
+    match arg {
+        _ if object.is_unwinding() => object,
+        Some(arg) if arg.is_unwinding() => arg,
+        arg => match object.function() {
+            Ok(function) => call(function, arg),
+            Err(e) => Object::Error(e),
+        },
+    }
+}
+
+/// Calls a function with its default value for as long as its next
+/// parameter has one. Only the callee's own parameters are filled, so a
+/// closure returned by the call is left as it is.
+fn fill_defaults(object: Object) -> Object {
+    match object {
+        Object::Function(function) if function.partial && function.has_default() => {
+            fill_defaults(call_function(function, None))
+        }
+        o => o,
+    }
+}
+
+/// Binds named arguments to the parameter of the same name. Names that do not
+/// match the next parameter are carried along until a later parameter does,
+/// and a name given twice or with no such parameter left is an error.
+fn bind_named(object: Object, mut named: Vec<(String, Object)>) -> Object {
+    if named.is_empty() {
+        return object;
+    }
+    match object {
+        Object::Function(mut function) => {
+            for (name, _) in named.iter() {
+                if function.named.iter().any(|(n, _)| n == name) {
+                    return Object::Error(format!("duplicate named argument: {}", name));
+                }
+                if !function.has_param(name) {
+                    return Object::Error(format!("unknown named argument: {}", name));
+                }
+            }
+            function.named.extend(named);
+            function.partial = true;
+            let position = match &function.param {
+                Some(Parameter {
+                    identifier: Identifier(param),
+                    rest: false,
+                    ..
+                }) => function.named.iter().position(|(name, _)| name == param),
+                _ => None,
+            };
+            match position {
+                Some(i) => {
+                    let (_, arg) = function.named.remove(i);
+                    call_function(function, Some(arg))
+                }
+                None => Object::Function(function),
+            }
+        }
+        Object::Error(e) => Object::Error(e),
+        _ => Object::Error(format!("unknown named argument: {}", named.remove(0).0)),
+    }
+}
+
+fn call(function: FunctionType, arg: Option<Object>) -> Object {
+    match function {
+        FunctionType::Function(f) => call_function(f, arg),
+        FunctionType::Builtin(f) => call_builtin(f, arg),
+        FunctionType::Composed(f) => call_composed(f, arg),
+        FunctionType::Memo(f) => call_memo(f, arg),
+    }
+}
+
+/// A rest parameter collects arguments until the function is called without
+/// one, which happens at the end of the call chain when defaults are filled.
+/// A variadic function returned by the body starts collecting afresh.
+fn call_function(mut function: Function, arg: Option<Object>) -> Object {
+    if function.is_variadic() {
+        if let Some(arg) = arg {
+            function.rest.push(arg);
+            function.partial = true;
+            return Object::Function(function);
+        }
+    }
+    let Function {
+        param,
+        body,
+        env,
+        named,
+        rest,
+        ..
+    } = function;
+    let mut child = Environment::from(Rc::clone(&env));
+    if let Some(Parameter {
+        identifier: Identifier(name),
+        default,
+        rest: variadic,
+    }) = param
+    {
+        let arg = match variadic {
+            true => Some(Object::Array(Array(rest))),
+            false => arg.or_else(|| default.map(|d| eval_expression(*d, &env))),
+        };
+        if let Some(arg) = arg {
+            child.set(&name, arg);
+        }
+    }
+    let context = env.borrow().context();
+    let depth = context.depth.get();
+    if let Some(max) = context.limits.max_call_depth {
+        if depth >= max {
+            return Object::Error(format!("maximum call depth of {} exceeded", max));
+        }
+    }
+    context.depth.set(depth + 1);
+    let object = eval_expression(body, &Rc::new(RefCell::new(child)));
+    context.depth.set(depth);
+    match object.returned() {
+        Object::Function(function) if !function.partial => match named.first() {
+            Some((name, _)) => Object::Error(format!("unknown named argument: {}", name)),
+            None => Object::Function(function),
+        },
+        object => bind_named(object, named),
+    }
+}
+
+fn call_composed(Composed { inner, outer }: Composed, arg: Option<Object>) -> Object {
+    match apply(*inner, arg) {
+        Object::Error(e) => Object::Error(e),
+        o => apply(*outer, Some(o)),
+    }
+}
+
+fn call_memo(Memo { function, cache }: Memo, arg: Option<Object>) -> Object {
+    let key = match arg.clone().map(Object::hash_key) {
+        Some(Ok(key)) => key,
+        _ => return apply(*function, arg),
+    };
+    if let Some(o) = cache.borrow().get(&key) {
+        return o.clone();
+    }
+    let object = apply(*function, arg);
+    if !matches!(object, Object::Error(_)) {
+        cache.borrow_mut().insert(key, object.clone());
+    }
+    object
+}
+
+fn call_builtin(mut builtin: Builtin, arg: Option<Object>) -> Object {
+    match arg {
+        Some(arg) => builtin.args.push(arg),
+        None => return invoke_builtin(builtin),
+    }
+    if builtin.is_saturated() {
+        invoke_builtin(builtin)
+    } else {
+        Object::Builtin(builtin)
+    }
+}
+
+fn invoke_builtin(
+    Builtin {
+        function,
+        args,
+        context,
+        ..
+    }: Builtin,
+) -> Object {
+    match function(args, &context) {
         Ok(o) => o,
-        Err(s) => Object::Error(s.to_string()),
+        Err(s) => Object::Error(s),
     }
 }
 
-fn eval_array(arr: ast::Array, env: &Rc<RefCell<Environment>>) -> Object {
-    let arr = arr
-        .iter()
-        .map(|e| eval_expression(e.clone(), env))
-        .collect();
+fn eval_array(ast::Array(elements): ast::Array, env: &Rc<RefCell<Environment>>) -> Object {
+    let mut arr = Vec::new();
+    for element in elements {
+        match element {
+            Expression::Spread(Spread(spread)) => match eval_spread(*spread, env) {
+                Object::Array(Array(spread)) => arr.extend(spread),
+                o => return o,
+            },
+            e => match eval_expression(e, env) {
+                o if o.is_unwinding() => return o,
+                o => arr.push(o),
+            },
+        }
+    }
     Object::Array(Array(arr))
 }
 
 fn eval_hash(hash: ast::Hash, env: &Rc<RefCell<Environment>>) -> Object {
-    let mut hashmap = HashMap::new();
+    let mut hashmap = IndexMap::new();
     for (k, v) in hash.iter() {
-        let key = eval_hash_key(k.clone());
-        let value = eval_expression(v.clone(), env);
-        hashmap.insert(key, value);
+        let key = match eval_expression(k.clone(), env) {
+            o if o.is_unwinding() => return o,
+            o => match o.hash_key() {
+                Ok(key) => key,
+                Err(e) => return Object::Error(e),
+            },
+        };
+        match eval_expression(v.clone(), env) {
+            o if o.is_unwinding() => return o,
+            o => hashmap.insert(key, o),
+        };
     }
     Object::Hash(Hash(hashmap))
 }
 
 fn eval_index(target: Expression, index: Expression, env: &Rc<RefCell<Environment>>) -> Object {
     let target = eval_expression(target, env);
+    if target.is_unwinding() {
+        return target;
+    }
     let index = eval_expression(index, env);
     match target {
+        _ if index.is_unwinding() => index,
         Object::Array(arr) => match index.integer() {
             Ok(i) => arr.get(i as usize).unwrap_or(&Object::Null).clone(),
             Err(e) => Object::Error(e),
         },
+        Object::String(s) => match index.integer() {
+            Ok(i) => s
+                .chars()
+                .nth(i as usize)
+                .map_or(Object::Null, |c| Object::String(c.to_string())),
+            Err(e) => Object::Error(e),
+        },
         Object::Hash(Hash(hash)) => match index.hash_key() {
-            Ok(k) => hash.get(&k.into()).unwrap_or(&Object::Null).clone(),
+            Ok(k) => hash.get(&k).unwrap_or(&Object::Null).clone(),
             Err(e) => Object::Error(e),
         },
         o => Object::Error(format!("unexpected index target: {}", o)),
     }
 }
+
+fn eval_field(
+    target: Expression,
+    Identifier(name): Identifier,
+    env: &Rc<RefCell<Environment>>,
+) -> Object {
+    match eval_expression(target, env) {
+        Object::Hash(Hash(hash)) => match hash.get(&Object::String(name.clone())) {
+            Some(o) => o.clone(),
+            None => Object::Error(format!("field not found: {}", name)),
+        },
+        o if o.is_unwinding() => o,
+        o => Object::Error(format!("unexpected field target: {}", o)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Capabilities;
+    use crate::object::*;
+    use crate::test_util::{capture, eval};
+
+    #[test]
+    fn hash_test() {
+        assert_eq!(eval("k = \"a\"; {[k]: 1}"), eval("{\"a\": 1}"));
+        assert_eq!(eval("{[1 + 1]: \"two\"}[2]"), eval("\"two\""));
+        assert_eq!(
+            eval("name = \"Sato\"; age = 20; {name, age}"),
+            eval("{\"name\": \"Sato\", \"age\": 20}")
+        );
+        assert_eq!(eval("a = 2; {a: 1, a}"), eval("{\"a\": 2}"));
+        assert_eq!(eval("name = 1; {name,}.name"), Object::Integer(1));
+        assert_eq!(eval("f = |x| { x }; f(1)"), Object::Integer(1));
+        assert_eq!(
+            eval("a = 1; b = 2; if true then { a } else { b }"),
+            Object::Integer(1)
+        );
+        assert_eq!(
+            eval("{[[1]]: 1}"),
+            Object::Error("[1] is not an hash-key".to_string())
+        );
+    }
+
+    #[test]
+    fn field_test() {
+        let person = "person = {\"name\": \"Sato\", \"job\": {\"title\": \"cook\"}};";
+        assert_eq!(eval(&format!("{} person.name", person)), eval("\"Sato\""));
+        assert_eq!(
+            eval(&format!("{} person.job.title", person)),
+            eval("\"cook\"")
+        );
+        assert_eq!(
+            eval(&format!("{} person.age", person)),
+            Object::Error("field not found: age".to_string())
+        );
+        assert_eq!(
+            eval("[1].name"),
+            Object::Error("unexpected field target: [1]".to_string())
+        );
+    }
+
+    #[test]
+    fn method_test() {
+        assert_eq!(
+            eval("[1, 2, 3, 4].map(|x| x * 10).filter(|x| x > 15)"),
+            eval("[20, 30, 40]")
+        );
+        assert_eq!(eval("\"a,b\".split(\",\").join(\"-\")"), eval("\"a-b\""));
+        assert_eq!(eval("add = |a, b| a + b; 1.add(2)"), Object::Integer(3));
+    }
+
+    #[test]
+    fn pipeline_test() {
+        assert_eq!(
+            eval("range(1, 5) |> map(|x| x * x) |> reduce(|a, b| a + b, 0)"),
+            Object::Integer(55)
+        );
+        assert_eq!(eval("add = |a, b| a + b; 2 |> add(1)"), Object::Integer(3));
+        assert_eq!(eval("[3, 1, 2] |> sort |> head"), Object::Integer(1));
+    }
+
+    #[test]
+    fn compose_test() {
+        let code = "inc = |x| x + 1; double = |x| x * 2;";
+        assert_eq!(
+            eval(&format!("{} (inc >> double)(3)", code)),
+            Object::Integer(8)
+        );
+        assert_eq!(
+            eval(&format!("{} (inc << double)(3)", code)),
+            Object::Integer(7)
+        );
+        assert_eq!(
+            eval("(trim >> upper >> len)(\" kani \")"),
+            Object::Integer(4)
+        );
+        assert_eq!(eval("map(upper >> reverse, [\"ab\"])"), eval("[\"BA\"]"));
+        assert_eq!(
+            format!("{}", eval("trim >> split(\",\")")),
+            "[built-in function: trim] >> [built-in function: split]"
+        );
+        assert_eq!(
+            eval("len >> 1"),
+            Object::Error("cannot compose [built-in function: len] and 1".to_string())
+        );
+    }
+
+    #[test]
+    fn apply_test() {
+        assert_eq!(eval("len $ \"ab\" + \"c\""), Object::Integer(3));
+        assert_eq!(eval("head $ reverse $ [1, 2, 3]"), Object::Integer(3));
+        let (mut evaluator, stdout) = capture(Capabilities::default());
+        assert_eq!(
+            evaluator
+                .eval_code("print $ [1, 2] |> map(|x| x * 2)")
+                .unwrap(),
+            Object::Null
+        );
+        assert_eq!(stdout.contents(), "[2, 4]\n");
+    }
+
+    #[test]
+    fn decorator_test() {
+        assert_eq!(
+            eval("twice = |f, x| f(f(x)); @twice inc = |x| x + 1; inc(0)"),
+            Object::Integer(2)
+        );
+        assert_eq!(
+            eval("add = |a, b| a + b; @add(1) @add(10) x = 100; x"),
+            Object::Integer(111)
+        );
+        assert_eq!(
+            eval("@memo fib = |n| if n < 2 then n else fib(n - 1) + fib(n - 2); fib(80)"),
+            Object::Integer(23416728348467685)
+        );
+    }
+
+    #[test]
+    fn section_test() {
+        assert_eq!(eval("map((* 2), [1, 2, 3])"), eval("[2, 4, 6]"));
+        assert_eq!(eval("map((10 -), [1, 2])"), eval("[9, 8]"));
+        assert_eq!(eval("reduce((+), 0, [1, 2, 3])"), Object::Integer(6));
+        assert_eq!(eval("filter((== 1), [1, 2, 1])"), eval("[1, 1]"));
+        assert_eq!(eval("(-)(5, 3)"), Object::Integer(2));
+        assert_eq!(eval("xs = [1]; ([0] +)(xs)"), eval("[0, 1]"));
+    }
+
+    #[test]
+    fn default_test() {
+        let f = "f = |a, b = 10| a + b;";
+        assert_eq!(eval(&format!("{} f(1)", f)), Object::Integer(11));
+        assert_eq!(eval(&format!("{} f(1, 2)", f)), Object::Integer(3));
+        assert_eq!(eval(&format!("{} f(1)(2)", f)), Object::Integer(3));
+        assert_eq!(eval(&format!("{} g = f(1); g", f)), Object::Integer(11));
+        assert_eq!(eval(&format!("{} 1 |> f", f)), Object::Integer(11));
+        assert_eq!(
+            eval("f = |a, b = a * 2, c = b + 1| [a, b, c]; f(1)"),
+            eval("[1, 2, 3]")
+        );
+        assert_eq!(
+            eval("f = |x, scale = 2| x * scale; map(f, [1, 2])"),
+            eval("[2, 4]")
+        );
+        assert_eq!(
+            eval("f = |acc, x, step = 1| acc + x * step; fold(f, 0, [1, 2])"),
+            Object::Integer(3)
+        );
+        let make = "make = |x| |y = 1| x + y;";
+        assert_eq!(eval(&format!("{} make(5)", make)).to_string(), "[function]");
+        assert_eq!(eval(&format!("{} make(5)(2)", make)), Object::Integer(7));
+        assert_eq!(eval(&format!("{} make(5)()", make)), Object::Integer(6));
+        assert_eq!(
+            eval(&format!("{} map(make, [1, 2]) |> len", make)),
+            Object::Integer(2)
+        );
+        assert_eq!(
+            eval("make = |x = 1| |y = 2| x + y; make()").to_string(),
+            "[function]"
+        );
+    }
+
+    #[test]
+    fn named_argument_test() {
+        let f = "f = |a, b = 10, c = 100| a + b + c;";
+        assert_eq!(eval(&format!("{} f(1, c: 0)", f)), Object::Integer(11));
+        assert_eq!(eval(&format!("{} f(c: 0, a: 1)", f)), Object::Integer(11));
+        assert_eq!(eval(&format!("{} f(b: 0)(1)", f)), Object::Integer(101));
+        assert_eq!(
+            eval(&format!("{} g = f(c: 0); g(1, 2)", f)),
+            Object::Integer(3)
+        );
+        assert_eq!(
+            eval(&format!("{} f(1, d: 0)", f)),
+            Object::Error("unknown named argument: d".to_string())
+        );
+        let make = "make = |x = 1| |y| x + y;";
+        assert_eq!(
+            eval(&format!("{} make(y: 2)", make)),
+            Object::Error("unknown named argument: y".to_string())
+        );
+        assert_eq!(eval(&format!("{} make()(y: 2)", make)), Object::Integer(3));
+        let unknown = Object::Error("unknown named argument: a".to_string());
+        assert_eq!(eval("f = |a, b| a + b; f(1, a: 2)"), unknown);
+        assert_eq!(eval("f = |a, b = 10| a + b; f(1, a: 2)"), unknown);
+        assert_eq!(eval("f = |a, b| a + b; f(a: 1, a: 2)"), unknown);
+        assert_eq!(eval("f = |a, b| a + b; f(a: 1)(a: 2)"), unknown);
+        assert_eq!(
+            eval(&format!("{} f(c: 0, c: 1)", f)),
+            Object::Error("duplicate named argument: c".to_string())
+        );
+        assert_eq!(
+            eval("len(s: \"kani\")"),
+            Object::Error("[built-in function: len] does not accept named arguments".to_string())
+        );
+    }
+
+    #[test]
+    fn rest_test() {
+        let sum = "sum = |..xs| fold((+), 0, xs);";
+        assert_eq!(eval(&format!("{} sum(1, 2, 3)", sum)), Object::Integer(6));
+        assert_eq!(eval(&format!("{} sum(1)(2, 3)", sum)), Object::Integer(6));
+        assert_eq!(eval(&format!("{} sum()", sum)), Object::Integer(0));
+        assert_eq!(eval(&format!("{} g = sum(1); g", sum)), Object::Integer(1));
+        assert_eq!(
+            eval("f = |first, ..rest| [first, rest]; f(1, 2, 3)"),
+            eval("[1, [2, 3]]")
+        );
+        assert_eq!(
+            eval("f = |first, ..rest| [first, rest]; f(1)"),
+            eval("[1, []]")
+        );
+        assert_eq!(eval("f = |..xs| xs; map(f, [1, 2])"), eval("[[1], [2]]"));
+        let f = "f = |..xs| |..ys| [xs, ys];";
+        assert_eq!(eval(&format!("{} f(1, 2)", f)).to_string(), "[function]");
+        assert_eq!(eval(&format!("{} f(1, 2)()(3)", f)), eval("[[1, 2], [3]]"));
+        assert_eq!(eval(&format!("{} f(1)()()", f)), eval("[[1], []]"));
+        assert_eq!(
+            eval("f = |..xs| |y| len(xs) + y; f(1, 2)()(10)"),
+            Object::Integer(12)
+        );
+    }
+
+    #[test]
+    fn spread_test() {
+        assert_eq!(
+            eval("a = [1, 2]; b = [3]; [0, ..a, ..b, 4]"),
+            eval("[0, 1, 2, 3, 4]")
+        );
+        assert_eq!(
+            eval("sum = |..xs| fold((+), 0, xs); sum(1, ..[2, 3], 4)"),
+            Object::Integer(10)
+        );
+        assert_eq!(
+            eval("add = |a, b| a + b; add(..[1, 2])"),
+            Object::Integer(3)
+        );
+        assert_eq!(eval("zip(..[[1], [2]])"), eval("[[1, 2]]"));
+        assert_eq!(eval("[..1]"), Object::Error("cannot spread 1".to_string()));
+    }
+
+    #[test]
+    fn template_test() {
+        assert_eq!(
+            eval("name = \"kani\"; age = 3; \"Hello ${name}, you are ${age + 1}\""),
+            eval("\"Hello kani, you are 4\"")
+        );
+        assert_eq!(
+            eval("xs = [1, 2]; \"${xs} ${{\"a\": 1}} ${xs.map(|x| \"<${x}>\").join(\"\")}\""),
+            eval("\"[1, 2] {a : 1} <1><2>\"")
+        );
+        assert_eq!(eval("\"cost: \\${1}\""), eval("\"cost: $\" + \"{1}\""));
+        assert_eq!(
+            eval("\"${missing}\""),
+            Object::Error("identifier not found: missing".to_string())
+        );
+    }
+
+    #[test]
+    fn hash_method_test() {
+        assert_eq!(
+            eval("[1, 2, 3].map(|x| x * 2).filter(|x| x > 2)"),
+            eval("filter(|x| x > 2, map(|x| x * 2, [1, 2, 3]))")
+        );
+        assert_eq!(
+            eval("h = {\"inc\": |x| x + 1}; h.inc(1)"),
+            Object::Integer(2)
+        );
+        assert_eq!(eval("h = {\"a\": 1}; h.keys()"), eval("[\"a\"]"));
+    }
+
+    #[test]
+    fn error_test() {
+        let error = Object::Error("identifier not found: missing".to_string());
+        assert_eq!(eval("x = missing; 1"), error);
+        assert_eq!(eval("f = |x| { missing; x }; f(1)"), error);
+        assert_eq!(eval("[missing, 1]"), error);
+        assert_eq!(eval("{\"a\": missing}"), error);
+        assert_eq!(eval("f = |x| 1; f(missing)"), error);
+        assert_eq!(eval("len(missing)"), error);
+        assert_eq!(eval("missing == 1"), error);
+        assert_eq!(eval("1 != missing"), error);
+        assert_eq!(eval("!missing"), error);
+        assert_eq!(eval("missing[0]"), error);
+        assert_eq!(eval("[1][missing]"), error);
+        assert_eq!(eval("missing.a"), error);
+    }
+}
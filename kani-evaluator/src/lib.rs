@@ -1,15 +1,26 @@
+mod builder;
 pub mod builtin;
+pub mod context;
 pub mod environment;
 mod evaluator;
 mod macros;
+pub mod module;
 pub mod object;
+pub mod output;
+pub mod prelude;
+#[cfg(test)]
+mod test_util;
 
+pub use builder::EvaluatorBuilder;
+pub use builtin::BuiltinModule;
+pub use context::{Capabilities, Limits};
 use environment::Environment;
 pub use evaluator::*;
 pub use kani_parser;
 use kani_parser::ast::Expression;
 pub use kani_parser::nom;
 use object::Object;
+pub use prelude::Prelude;
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -23,12 +34,24 @@ impl Default for Evaluator {
     }
 }
 
-impl Evaluator {
-    pub fn new() -> Self {
+impl From<Environment> for Evaluator {
+    fn from(env: Environment) -> Self {
         Self {
-            env: Rc::new(RefCell::new(Environment::default())),
+            env: Rc::new(RefCell::new(env)),
         }
     }
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Self::builder()
+            .build()
+            .expect("the standard prelude is valid")
+    }
+
+    pub fn builder() -> EvaluatorBuilder {
+        EvaluatorBuilder::new()
+    }
 
     pub fn eval_code<'a>(
         &mut self,
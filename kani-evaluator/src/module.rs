@@ -0,0 +1,259 @@
+//! Imported files are evaluated once, in their own environment, and cached by
+//! canonical path. Their top-level assignments become a hash of exports.
+
+use crate::builtin::{allowed, builtins};
+use crate::context::Context;
+use crate::environment::Environment;
+use crate::evaluator::eval_expression;
+use crate::object::*;
+use kani_parser::program;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+#[derive(Default)]
+pub struct Modules {
+    cache: HashMap<PathBuf, Object>,
+    loading: Vec<PathBuf>,
+}
+
+/// Resolves `path` relative to the directory of the importing file, or to the
+/// working directory when the code does not come from a file.
+pub fn resolve(path: &str, env: &Environment) -> PathBuf {
+    match env.path().as_deref().and_then(Path::parent) {
+        Some(dir) => dir.join(path),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Importing reads a file, so the path must lie in a directory the
+/// evaluator may read. It is checked before anything else, so that a denied
+/// import does not reveal whether the file exists.
+pub fn import(path: &str, env: &Rc<RefCell<Environment>>) -> Object {
+    let context = env.borrow().context();
+    let resolved = resolve(path, &env.borrow());
+    let resolved = allowed(
+        &resolved.to_string_lossy(),
+        &context.capabilities.read,
+        "import",
+    )
+    .and_then(|resolved| {
+        resolved
+            .canonicalize()
+            .map_err(|e| format!("cannot import {}: {}", path, e))
+    });
+    let path = match resolved {
+        Ok(path) => path,
+        Err(e) => return Object::Error(e),
+    };
+    if let Some(exports) = context.modules.borrow().cache.get(&path) {
+        return exports.clone();
+    }
+    if let Some(i) = context
+        .modules
+        .borrow()
+        .loading
+        .iter()
+        .position(|p| *p == path)
+    {
+        let cycle = context.modules.borrow().loading[i..]
+            .iter()
+            .chain(Some(&path))
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        return Object::Error(format!("circular import: {}", cycle));
+    }
+    let code = match fs::read_to_string(&path) {
+        Ok(code) => code,
+        Err(e) => return Object::Error(format!("cannot import {}: {}", path.display(), e)),
+    };
+    context.modules.borrow_mut().loading.push(path.clone());
+    let exports = eval_module(path.clone(), &code, &context);
+    context.modules.borrow_mut().loading.pop();
+    if !exports.is_unwinding() {
+        context
+            .modules
+            .borrow_mut()
+            .cache
+            .insert(path, exports.clone());
+    }
+    exports
+}
+
+/// Unlike a script, a module stops at the first top-level error so that a
+/// failed import fails the modules that depend on it.
+fn eval_module(path: PathBuf, code: &str, context: &Rc<Context>) -> Object {
+    let program = match program(code) {
+        Ok((_, program)) => program,
+        Err(_) => return Object::Error(format!("parser error in {}", path.display())),
+    };
+    let globals = context.globals.borrow().upgrade();
+    let globals = globals.unwrap_or_else(|| {
+        let env = Environment::with_builtins(builtins(), Rc::clone(context));
+        Rc::new(RefCell::new(env))
+    });
+    let env = Rc::new(RefCell::new(Environment::from(globals).with_path(path)));
+    for expression in program {
+        match eval_expression(expression, &env) {
+            o if o.is_returned() => break,
+            o if o.is_unwinding() => return o,
+            _ => {}
+        }
+    }
+    let exports = env.borrow().exports();
+    Object::Hash(Hash(
+        exports
+            .into_iter()
+            .map(|(name, o)| (Object::String(name), o))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Capabilities;
+    use crate::object::*;
+    use crate::test_util::TempDir;
+    use crate::Evaluator;
+
+    fn eval(dir: &TempDir, code: &str) -> Object {
+        let capabilities = Capabilities {
+            read: vec![dir.to_path_buf()],
+            ..Capabilities::default()
+        };
+        let evaluator = Evaluator::builder()
+            .path(dir.join("main.kn"))
+            .capabilities(capabilities)
+            .build();
+        evaluator.unwrap().eval_code(code).unwrap()
+    }
+
+    #[test]
+    fn import_test() {
+        let dir = TempDir::new(
+            "import",
+            &[(
+                "lib/math.kn",
+                "double = |x| x * 2; twice = |f, x| f(f(x)); total = sum([1, 2])",
+            )],
+        );
+        assert_eq!(
+            eval(&dir, "import \"lib/math.kn\"; twice(double, 1)"),
+            Object::Integer(4)
+        );
+        assert_eq!(
+            eval(
+                &dir,
+                "import math from \"lib/math.kn\"; math.twice(math.double, 3)"
+            ),
+            Object::Integer(12)
+        );
+        assert_eq!(
+            eval(
+                &dir,
+                "import math from \"lib/math.kn\"; [1, 2].map(math.double)"
+            ),
+            eval(&dir, "[2, 4]")
+        );
+        assert_eq!(
+            eval(&dir, "import math from \"lib/math.kn\"; keys(math)"),
+            eval(&dir, "[\"double\", \"twice\", \"total\"]")
+        );
+        assert_eq!(
+            eval(&dir, "import {double} from \"lib/math.kn\"; double(5)"),
+            Object::Integer(10)
+        );
+        assert_eq!(
+            eval(&dir, "import {triple} from \"lib/math.kn\""),
+            Object::Error("triple is not exported by lib/math.kn".to_string())
+        );
+    }
+
+    #[test]
+    fn relative_test() {
+        let dir = TempDir::new(
+            "relative",
+            &[
+                ("lib/a.kn", "import {b} from \"b.kn\"; a = b + 1"),
+                ("lib/b.kn", "b = 1"),
+            ],
+        );
+        assert_eq!(
+            eval(&dir, "import {a} from \"lib/a.kn\"; a"),
+            Object::Integer(2)
+        );
+        assert_eq!(
+            eval(&dir, "import a from \"lib/a.kn\"; keys(a)"),
+            eval(&dir, "[\"a\"]")
+        );
+    }
+
+    #[test]
+    fn error_test() {
+        let dir = TempDir::new(
+            "error",
+            &[
+                ("a.kn", "import \"b.kn\""),
+                ("b.kn", "import \"a.kn\""),
+                ("broken.kn", "x = missing"),
+            ],
+        );
+        match eval(&dir, "import \"a.kn\"") {
+            Object::Error(e) => assert!(e.starts_with("circular import: "), "{}", e),
+            o => panic!("unexpected {}", o),
+        }
+        assert_eq!(
+            eval(&dir, "import \"broken.kn\""),
+            Object::Error("identifier not found: missing".to_string())
+        );
+        match eval(&dir, "import \"nowhere.kn\"") {
+            Object::Error(e) => assert!(e.starts_with("cannot import nowhere.kn"), "{}", e),
+            o => panic!("unexpected {}", o),
+        }
+    }
+
+    #[test]
+    fn denied_test() {
+        let dir = TempDir::new(
+            "denied",
+            &[("lib/math.kn", "double = |x| x * 2"), ("main.kn", "")],
+        );
+        let evaluator = Evaluator::builder().path(dir.join("main.kn")).build();
+        let mut evaluator = evaluator.unwrap();
+        for path in &["lib/math.kn", "lib/missing.kn"] {
+            let denied = format!(
+                "permission denied: cannot import {}",
+                dir.join(path).display()
+            );
+            assert_eq!(
+                evaluator
+                    .eval_code(&format!("import \"{}\"", path))
+                    .unwrap(),
+                Object::Error(denied)
+            );
+        }
+        let capabilities = Capabilities {
+            read: vec![dir.join("lib")],
+            ..Capabilities::default()
+        };
+        let evaluator = Evaluator::builder()
+            .path(dir.join("main.kn"))
+            .capabilities(capabilities)
+            .build();
+        let code = "import {double} from \"lib/math.kn\"; double(2)";
+        assert_eq!(
+            evaluator.unwrap().eval_code(code).unwrap(),
+            Object::Integer(4)
+        );
+        assert_eq!(
+            eval(&dir, "import \"../outside.kn\""),
+            Object::Error(format!(
+                "permission denied: cannot import {}",
+                dir.join("../outside.kn").display()
+            ))
+        );
+    }
+}
@@ -1,8 +1,9 @@
+use super::context::Context;
 use super::environment::Environment;
 use crate::{impl_deref, impl_from_enum};
-use kani_parser::ast::{Expression, HashKey, Identifier, Literal};
+use indexmap::IndexMap;
+use kani_parser::ast::{self, Expression, Identifier, Literal, Parameter};
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::fmt;
 use std::hash;
 use std::ops::{Add, Div, Mul, Neg, Not, Rem, Sub};
@@ -18,8 +19,13 @@ pub enum Object {
     Hash(Hash),
     Function(Function),
     Builtin(Builtin),
+    Composed(Composed),
+    Memo(Memo),
     Null,
     Return(Return),
+    /// Raised by `exit(n)` and passed up like a `return` until it leaves the
+    /// program, so that the host decides how to end the process.
+    Exit(i32),
     Error(String),
 }
 
@@ -32,38 +38,69 @@ pub enum Number {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Array(pub Vec<Object>);
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Hash(pub HashMap<Object, Object>);
+#[derive(Debug, Clone)]
+pub struct Hash(pub IndexMap<Object, Object>);
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Return(pub Box<Object>);
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Function {
-    pub param: Option<Identifier>,
+    pub param: Option<Parameter>,
     pub body: Expression,
     pub env: Rc<RefCell<Environment>>,
+    pub named: Vec<(String, Object)>,
+    pub rest: Vec<Object>,
+    /// Whether this function is partway through a call, so that the call
+    /// fills its remaining defaults instead of returning it as a closure.
+    pub partial: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Builtin {
     pub name: String,
+    pub arity: usize,
+    pub args: Vec<Object>,
     pub function: BuiltinFunction,
+    /// The evaluator this builtin was installed into, for output and limits.
+    pub context: Rc<Context>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Composed {
+    pub inner: Box<Object>,
+    pub outer: Box<Object>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Memo {
+    pub function: Box<Object>,
+    pub cache: Rc<RefCell<IndexMap<Object, Object>>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum FunctionType {
     Function(Function),
     Builtin(Builtin),
+    Composed(Composed),
+    Memo(Memo),
 }
 
-pub type BuiltinFunction = fn(Option<Object>) -> Result<Object, &'static str>;
+pub type BuiltinFunction = fn(Vec<Object>, &Context) -> Result<Object, String>;
 
 impl Object {
     pub fn is_returned(&self) -> bool {
         matches!(*self, Object::Return(_))
     }
 
+    /// Whether evaluation should stop here: a `return`, an exit or an error.
+    pub fn is_unwinding(&self) -> bool {
+        matches!(
+            *self,
+            Object::Return(_) | Object::Exit(_) | Object::Error(_)
+        )
+    }
+
     pub fn returned(self) -> Self {
         match self {
             Self::Return(Return(o)) => *o,
@@ -91,11 +128,33 @@ impl Object {
         match self {
             Self::Function(f) => Ok(FunctionType::Function(f)),
             Self::Builtin(b) => Ok(FunctionType::Builtin(b)),
+            Self::Composed(c) => Ok(FunctionType::Composed(c)),
+            Self::Memo(m) => Ok(FunctionType::Memo(m)),
             Self::Error(e) => Err(e),
             o => Err(format!("{} is not an function", o)),
         }
     }
 
+    pub fn is_callable(&self) -> bool {
+        matches!(
+            *self,
+            Object::Function(_) | Object::Builtin(_) | Object::Composed(_) | Object::Memo(_)
+        )
+    }
+
+    pub fn compose(self, outer: Self) -> Self {
+        match (self, outer) {
+            (Self::Error(s), _) | (_, Self::Error(s)) => Self::Error(s),
+            (inner, outer) if inner.is_callable() && outer.is_callable() => {
+                Self::Composed(Composed {
+                    inner: Box::new(inner),
+                    outer: Box::new(outer),
+                })
+            }
+            (l, r) => Self::Error(format!("cannot compose {} and {}", l, r)),
+        }
+    }
+
     pub fn literal(self) -> Result<Literal, String> {
         match self {
             Self::Integer(i) => Ok(Literal::Int(i)),
@@ -107,11 +166,10 @@ impl Object {
         }
     }
 
-    pub fn hash_key(self) -> Result<HashKey, String> {
+    /// Checks that the object can be used as a hash key.
+    pub fn hash_key(self) -> Result<Self, String> {
         match self {
-            Self::Integer(i) => Ok(HashKey::Int(i)),
-            Self::Boolean(b) => Ok(HashKey::Bool(b)),
-            Self::String(s) => Ok(HashKey::Str(s)),
+            Self::Integer(_) | Self::Boolean(_) | Self::String(_) => Ok(self),
             Self::Error(e) => Err(e),
             o => Err(format!("{} is not an hash-key", o)),
         }
@@ -176,18 +234,97 @@ impl Object {
 }
 
 impl Function {
-    pub fn new(param: Option<Identifier>, body: Expression, env: Rc<RefCell<Environment>>) -> Self {
-        Self { param, body, env }
+    pub fn new(param: Option<Parameter>, body: Expression, env: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            param,
+            body,
+            env,
+            named: Vec::new(),
+            rest: Vec::new(),
+            partial: false,
+        }
+    }
+
+    /// A rest parameter defaults to the arguments it has collected so far.
+    pub fn has_default(&self) -> bool {
+        matches!(
+            &self.param,
+            Some(Parameter {
+                default: Some(_),
+                ..
+            }) | Some(Parameter { rest: true, .. })
+        )
+    }
+
+    pub fn is_variadic(&self) -> bool {
+        matches!(&self.param, Some(Parameter { rest: true, .. }))
+    }
+
+    /// Whether a parameter still to be bound, this function's own or one of
+    /// the curried functions continuing its parameter list, has this name.
+    pub fn has_param(&self, name: &str) -> bool {
+        let (mut param, mut body) = (&self.param, &self.body);
+        loop {
+            if let Some(Parameter {
+                identifier: Identifier(p),
+                rest: false,
+                ..
+            }) = param
+            {
+                if p == name {
+                    return true;
+                }
+            }
+            match body {
+                Expression::Function(ast::Function {
+                    param: p,
+                    body: b,
+                    curried: true,
+                }) => {
+                    param = p;
+                    body = b;
+                }
+                _ => return false,
+            }
+        }
+    }
+}
+
+impl Memo {
+    pub fn new(function: Object) -> Self {
+        Self {
+            function: Box::new(function),
+            cache: Rc::new(RefCell::new(IndexMap::new())),
+        }
     }
 }
 
 impl Builtin {
-    pub fn new(name: &str, function: BuiltinFunction) -> Self {
+    pub fn new(name: &str, arity: usize, function: BuiltinFunction) -> Self {
         Self {
             name: name.to_string(),
+            arity,
+            args: Vec::new(),
             function,
+            context: Rc::default(),
         }
     }
+
+    pub fn is_saturated(&self) -> bool {
+        self.args.len() >= self.arity
+    }
+}
+
+impl PartialEq for Hash {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.args == other.args
+    }
 }
 
 impl fmt::Display for Object {
@@ -201,8 +338,11 @@ impl fmt::Display for Object {
             Self::Hash(h) => h.fmt(f),
             Self::Function(x) => x.fmt(f),
             Self::Builtin(b) => b.fmt(f),
+            Self::Composed(c) => c.fmt(f),
+            Self::Memo(m) => m.fmt(f),
             Self::Null => write!(f, "null"),
             Self::Return(o) => o.fmt(f),
+            Self::Exit(code) => write!(f, "exit({})", code),
             Self::Error(s) => write!(f, "Error: {}", s),
         }
     }
@@ -252,6 +392,18 @@ impl fmt::Display for Builtin {
     }
 }
 
+impl fmt::Display for Composed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} >> {}", self.inner, self.outer)
+    }
+}
+
+impl fmt::Display for Memo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[memoized: {}]", self.function)
+    }
+}
+
 impl Eq for Object {}
 
 impl hash::Hash for Object {
@@ -265,24 +417,16 @@ impl hash::Hash for Object {
     }
 }
 
-impl From<HashKey> for Object {
-    fn from(key: HashKey) -> Self {
-        match key {
-            HashKey::Int(i) => Self::Integer(i),
-            HashKey::Bool(b) => Self::Boolean(b),
-            HashKey::Str(s) => Self::String(s),
-        }
-    }
-}
-
 impl_from_enum!(Object::Array, Array);
 impl_from_enum!(Object::Function, Function);
 impl_from_enum!(Object::Builtin, Builtin);
+impl_from_enum!(Object::Composed, Composed);
+impl_from_enum!(Object::Memo, Memo);
 impl_from_enum!(Object::Hash, Hash);
 impl_from_enum!(Object::Return, Return);
 
 impl_deref!(Array, Vec<Object>);
-impl_deref!(Hash, HashMap<Object, Object>);
+impl_deref!(Hash, IndexMap<Object, Object>);
 impl_deref!(Return, Box<Object>);
 
 impl Not for Object {
@@ -0,0 +1,56 @@
+//! Where `print` and the other output builtins write to. Hosts that embed an
+//! evaluator can capture output with a [`Buffer`] or any `FnMut(&str)`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub trait Sink {
+    fn write_line(&mut self, line: &str);
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stdout;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stderr;
+
+/// Collects output in memory. Clones share the same contents, so a host can
+/// keep one clone and hand the other to the evaluator.
+#[derive(Debug, Clone, Default)]
+pub struct Buffer(Rc<RefCell<String>>);
+
+impl Sink for Stdout {
+    fn write_line(&mut self, line: &str) {
+        println!("{}", line);
+    }
+}
+
+impl Sink for Stderr {
+    fn write_line(&mut self, line: &str) {
+        eprintln!("{}", line);
+    }
+}
+
+impl Buffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contents(&self) -> String {
+        self.0.borrow().clone()
+    }
+}
+
+impl Sink for Buffer {
+    fn write_line(&mut self, line: &str) {
+        let mut contents = self.0.borrow_mut();
+        contents.push_str(line);
+        contents.push('\n');
+    }
+}
+
+impl<F: FnMut(&str)> Sink for F {
+    fn write_line(&mut self, line: &str) {
+        self(line)
+    }
+}
@@ -0,0 +1,80 @@
+//! The prelude is kani source evaluated into the global scope after the native
+//! builtins. Collection primitives such as `map` and `range` stay native; the
+//! prelude adds the helpers that are easiest to write in kani itself.
+
+use crate::environment::Environment;
+use crate::evaluator::eval_expression;
+use crate::object::Object;
+use kani_parser::program;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub const STANDARD: &str = include_str!("prelude.kn");
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Prelude {
+    #[default]
+    Standard,
+    Empty,
+    Custom(String),
+}
+
+impl Prelude {
+    pub fn source(&self) -> &str {
+        match self {
+            Self::Standard => STANDARD,
+            Self::Empty => "",
+            Self::Custom(source) => source,
+        }
+    }
+
+    pub fn load(&self, env: &Rc<RefCell<Environment>>) -> Result<(), String> {
+        let program = match program(self.source()) {
+            Ok((_, program)) => program,
+            Err(_) => return Err("parser error in prelude".to_string()),
+        };
+        for expression in program {
+            if let Object::Error(e) = eval_expression(expression, env) {
+                return Err(format!("error in prelude: {}", e));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::eval;
+    use crate::Evaluator;
+
+    #[test]
+    fn standard_test() {
+        assert_eq!(eval("identity(1)"), Object::Integer(1));
+        assert_eq!(eval("constant(1, 2)"), Object::Integer(1));
+        assert_eq!(eval("flip((-), 1, 3)"), Object::Integer(2));
+        assert_eq!(eval("compose((* 2), (+ 1))(3)"), Object::Integer(8));
+        assert_eq!(eval("sum(range(1, 4))"), Object::Integer(10));
+        assert_eq!(eval("product(range(1, 4))"), Object::Integer(24));
+        assert_eq!(eval("max(3, 1, 4)"), Object::Integer(4));
+        assert_eq!(eval("min(..[3, 1, 4])"), Object::Integer(1));
+        assert_eq!(eval("concat([1], [], [2, 3])"), eval("[1, 2, 3]"));
+        assert_eq!(eval("count((> 1), [1, 2, 3])"), Object::Integer(2));
+        assert_eq!(eval("last([1, 2, 3])"), Object::Integer(3));
+    }
+
+    #[test]
+    fn builder_test() {
+        let with = |prelude| Evaluator::builder().prelude(prelude).build();
+        let mut evaluator = with(Prelude::Empty).unwrap();
+        assert_eq!(
+            evaluator.eval_code("identity(1)").unwrap(),
+            Object::Error("identifier not found: identity".to_string())
+        );
+        assert_eq!(evaluator.eval_code("len([1])").unwrap(), Object::Integer(1));
+        let mut evaluator = with(Prelude::Custom("answer = 42".to_string())).unwrap();
+        assert_eq!(evaluator.eval_code("answer").unwrap(), Object::Integer(42));
+        assert!(with(Prelude::Custom("x = ".to_string())).is_err());
+        assert!(with(Prelude::Custom("x = missing".to_string())).is_err());
+    }
+}
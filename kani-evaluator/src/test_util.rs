@@ -0,0 +1,61 @@
+//! Helpers shared by the unit tests. Evaluators made here write to a
+//! [`Buffer`], so tests never print to the process stdout.
+
+use crate::context::Capabilities;
+use crate::object::Object;
+use crate::output::Buffer;
+use crate::Evaluator;
+use std::fs;
+use std::ops::Deref;
+use std::path::PathBuf;
+
+pub fn eval(code: &str) -> Object {
+    eval_with(Capabilities::default(), code)
+}
+
+pub fn eval_with(capabilities: Capabilities, code: &str) -> Object {
+    let (mut evaluator, _) = capture(capabilities);
+    evaluator.eval_code(code).unwrap()
+}
+
+/// An evaluator whose stdout is kept in the returned buffer.
+pub fn capture(capabilities: Capabilities) -> (Evaluator, Buffer) {
+    let stdout = Buffer::new();
+    let evaluator = Evaluator::builder()
+        .capabilities(capabilities)
+        .stdout(stdout.clone())
+        .stderr(Buffer::new())
+        .build()
+        .unwrap();
+    (evaluator, stdout)
+}
+
+/// A temporary directory holding the given files, removed when dropped.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str, files: &[(&str, &str)]) -> Self {
+        let dir = std::env::temp_dir().join(format!("kani-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (path, contents) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        TempDir(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = PathBuf;
+
+    fn deref(&self) -> &PathBuf {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -8,6 +8,7 @@ pub enum Expression {
     Return(Return),
     Identifier(Identifier),
     Literal(Literal),
+    Template(Template),
     Prefix(Prefix),
     Postfix(Postfix),
     Infix(Infix),
@@ -15,6 +16,8 @@ pub enum Expression {
     Function(Function),
     Array(Array),
     Hash(Hash),
+    Spread(Spread),
+    Import(Import),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -22,6 +25,7 @@ pub struct Block(pub Vec<Expression>);
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Assign {
+    pub decorators: Vec<Expression>,
     pub identifier: Identifier,
     pub expression: Box<Expression>,
 }
@@ -40,6 +44,15 @@ pub enum Literal {
     Bool(Bool),
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template(pub Vec<TemplatePart>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePart {
+    Str(String),
+    Expression(Expression),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Prefix {
     pub operator: PrefixOperator,
@@ -68,21 +81,41 @@ pub struct If {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Function {
-    pub param: Option<Identifier>,
+    pub param: Option<Parameter>,
     pub body: Box<Expression>,
+    /// Whether this function continues the parameter list of the function
+    /// whose body it is, as `|b|` does in `|a, b| a + b` but not in
+    /// `|a| |b| a + b`.
+    pub curried: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub identifier: Identifier,
+    pub default: Option<Box<Expression>>,
+    pub rest: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Array(pub Vec<Expression>);
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct Hash(pub Vec<(HashKey, Expression)>);
+pub struct Hash(pub Vec<(Expression, Expression)>);
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum HashKey {
-    Str(String),
-    Int(Int),
-    Bool(Bool),
+pub struct Spread(pub Box<Expression>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub path: String,
+    pub binding: ImportBinding,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportBinding {
+    All,
+    Namespace(Identifier),
+    Names(Vec<Identifier>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -95,15 +128,33 @@ pub enum PrefixOperator {
 #[derive(Debug, Clone, PartialEq)]
 pub enum PostfixOperator {
     Call(Argument),
+    NamedCall(NamedArgument),
     Index(Index),
+    Field(Field),
+    Method(Method),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Argument(pub Option<Box<Expression>>);
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedArgument {
+    pub name: Identifier,
+    pub expression: Box<Expression>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Index(pub Box<Expression>);
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field(pub Identifier);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Method {
+    pub name: Identifier,
+    pub arguments: Vec<PostfixOperator>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum InfixOperator {
     Plus,
@@ -117,13 +168,20 @@ pub enum InfixOperator {
     LessThanEqual,
     GreaterThan,
     LessThan,
+    Pipeline,
+    ComposeRight,
+    ComposeLeft,
+    Apply,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 pub enum Precedence {
     Lowest,
+    Apply,
+    Pipeline,
     Equals,
     LessGreater,
+    Compose,
     Sum,
     Product,
     Prefix,
@@ -136,6 +194,7 @@ impl_from_enum!(Expression::Assign, Assign);
 impl_from_enum!(Expression::Return, Return);
 impl_from_enum!(Expression::Identifier, Identifier);
 impl_from_enum!(Expression::Literal, Literal);
+impl_from_enum!(Expression::Template, Template);
 impl_from_enum!(Expression::Prefix, Prefix);
 impl_from_enum!(Expression::Postfix, Postfix);
 impl_from_enum!(Expression::Infix, Infix);
@@ -143,21 +202,38 @@ impl_from_enum!(Expression::If, If);
 impl_from_enum!(Expression::Function, Function);
 impl_from_enum!(Expression::Array, Array);
 impl_from_enum!(Expression::Hash, Hash);
+impl_from_enum!(Expression::Spread, Spread);
+impl_from_enum!(Expression::Import, Import);
 
 impl_from_enum!(PostfixOperator::Call, Argument);
+impl_from_enum!(PostfixOperator::NamedCall, NamedArgument);
 impl_from_enum!(PostfixOperator::Index, Index);
+impl_from_enum!(PostfixOperator::Field, Field);
+impl_from_enum!(PostfixOperator::Method, Method);
 
 impl_deref!(Block, Vec<Expression>);
+impl_deref!(Template, Vec<TemplatePart>);
 impl_deref!(Return, Expression);
 impl_deref!(Identifier, String);
 impl_deref!(Array, Vec<Expression>);
-impl_deref!(Hash, Vec<(HashKey, Expression)>);
+impl_deref!(Hash, Vec<(Expression, Expression)>);
+impl_deref!(Spread, Box<Expression>);
 impl_deref!(Argument, Option<Box<Expression>>);
 impl_deref!(Index, Box<Expression>);
+impl_deref!(Field, Identifier);
 
 impl Assign {
     pub fn new(identifier: Identifier, expression: Expression) -> Self {
+        Self::decorated(Vec::new(), identifier, expression)
+    }
+
+    pub fn decorated(
+        decorators: Vec<Expression>,
+        identifier: Identifier,
+        expression: Expression,
+    ) -> Self {
         Self {
+            decorators,
             identifier,
             expression: Box::from(expression),
         }
@@ -194,6 +270,29 @@ impl Postfix {
             ),
         }
     }
+
+    pub fn calling(mut operators: Vec<PostfixOperator>, expression: Expression) -> Self {
+        let last = operators.pop().unwrap_or_else(|| Argument(None).into());
+        let expression = operators
+            .into_iter()
+            .fold(expression, |e, operator| Self::new(operator, e).into());
+        Self::new(last, expression)
+    }
+}
+
+impl Method {
+    pub fn new(name: Identifier, arguments: Vec<PostfixOperator>) -> Self {
+        Self { name, arguments }
+    }
+}
+
+impl NamedArgument {
+    pub fn new(name: Identifier, expression: Expression) -> Self {
+        Self {
+            name,
+            expression: Box::new(expression),
+        }
+    }
 }
 
 impl Infix {
@@ -221,18 +320,69 @@ impl If {
 }
 
 impl Function {
-    pub fn new(param: Option<Identifier>, body: Box<Expression>) -> Self {
-        Self { param, body }
+    pub fn new(param: Option<Parameter>, body: Box<Expression>) -> Self {
+        Self {
+            param,
+            body,
+            curried: false,
+        }
+    }
+
+    pub fn section(
+        operator: InfixOperator,
+        left: Option<Expression>,
+        right: Option<Expression>,
+    ) -> Self {
+        let mut params = Vec::new();
+        let mut operand = |expression: Option<Expression>, name: &str| {
+            expression.unwrap_or_else(|| {
+                params.push(Parameter::new(Identifier(name.to_string())));
+                Identifier(name.to_string()).into()
+            })
+        };
+        let left = operand(left, "$left");
+        let right = operand(right, "$right");
+        let body = Infix::new(operator, left, right);
+        Self::currying(&params, Box::new(body.into()))
     }
 
-    pub fn currying(params: &[Identifier], body: Box<Expression>) -> Self {
+    pub fn currying(params: &[Parameter], body: Box<Expression>) -> Self {
         match params.len() {
-            0 => Self { param: None, body },
+            0 => Self::new(None, body),
             1 => Self::new(Some(params[0].clone()), body),
-            _ => Self::new(
-                Some(params[0].clone()),
-                Box::new(Self::currying(&params[1..], body).into()),
-            ),
+            _ => {
+                let rest = Self {
+                    curried: true,
+                    ..Self::currying(&params[1..], body)
+                };
+                Self::new(Some(params[0].clone()), Box::new(rest.into()))
+            }
+        }
+    }
+}
+
+impl Parameter {
+    pub fn new(identifier: Identifier) -> Self {
+        Self {
+            identifier,
+            default: None,
+            rest: false,
+        }
+    }
+
+    pub fn with_default(identifier: Identifier, default: Expression) -> Self {
+        Self {
+            identifier,
+            default: Some(Box::new(default)),
+            rest: false,
+        }
+    }
+
+    pub fn rest(identifier: Identifier) -> Self {
+        Self {
+            identifier,
+            default: None,
+            rest: true,
         }
     }
 }
@@ -0,0 +1,43 @@
+//! nom's own error reports whichever alternative of an `alt` failed last,
+//! which is usually the start of an expression. This one keeps the
+//! alternative that got furthest, so errors point at the offending token.
+
+use nom::error::{ErrorKind, FromExternalError, ParseError};
+use nom::InputLength;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error<I> {
+    pub input: I,
+    pub code: ErrorKind,
+}
+
+pub type IResult<I, O> = nom::IResult<I, O, Error<I>>;
+
+impl<I: InputLength> ParseError<I> for Error<I> {
+    fn from_error_kind(input: I, code: ErrorKind) -> Self {
+        Self { input, code }
+    }
+
+    fn append(_: I, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn or(self, other: Self) -> Self {
+        match self.input.input_len() < other.input.input_len() {
+            true => self,
+            false => other,
+        }
+    }
+}
+
+impl<I: InputLength, E> FromExternalError<I, E> for Error<I> {
+    fn from_external_error(input: I, code: ErrorKind, _: E) -> Self {
+        Self::from_error_kind(input, code)
+    }
+}
+
+impl<I> From<Error<I>> for nom::error::Error<I> {
+    fn from(Error { input, code }: Error<I>) -> Self {
+        Self::new(input, code)
+    }
+}
@@ -0,0 +1,511 @@
+//! Formats kani source from its lossless token stream rather than its AST, so
+//! that comments and the author's line breaks survive. What is canonical is
+//! the spacing between tokens on a line and the indentation of each line.
+
+use crate::error::IResult;
+use crate::lexer::{lexemes, shebang, token, Lexeme, Trivia};
+use crate::parser::program;
+use crate::token::Token;
+use nom::combinator::opt;
+use std::ops::Range;
+
+const INDENT: usize = 4;
+
+/// Fails with the parser error if `source` is not a valid program.
+pub fn format(source: &str) -> Result<String, nom::Err<nom::error::Error<&str>>> {
+    program(source)?;
+    let (body, shebang) = opt(shebang)(source).map_err(|e| e.map(Into::into))?;
+    let (_, (lexemes, trailing)) = lexemes(body).map_err(|e| e.map(Into::into))?;
+    let mut formatter = Formatter::new(&lexemes);
+    if let Some(shebang) = shebang {
+        formatter.out.push_str(shebang.trim_end());
+        formatter.out.push('\n');
+    }
+    for line in lines(&lexemes, &trailing) {
+        formatter.line(line);
+    }
+    Ok(formatter.finish())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Line<'a> {
+    Code {
+        tokens: Range<usize>,
+        comment: Option<&'a str>,
+    },
+    Comment(&'a str),
+    Blank,
+}
+
+/// Splits the lexemes into lines as the author wrote them.
+fn lines<'a>(lexemes: &[Lexeme<'a>], trailing: &[Trivia<'a>]) -> Vec<Line<'a>> {
+    let mut lines = Vec::new();
+    let mut code = None;
+    for (i, lexeme) in lexemes.iter().enumerate() {
+        breaks(&lexeme.trivia, &mut code, &mut lines);
+        match &mut code {
+            Some(Line::Code { tokens, .. }) => tokens.end = i + 1,
+            _ => {
+                code = Some(Line::Code {
+                    tokens: i..i + 1,
+                    comment: None,
+                })
+            }
+        }
+    }
+    breaks(trailing, &mut code, &mut lines);
+    lines.extend(code);
+    lines
+}
+
+fn breaks<'a>(trivia: &[Trivia<'a>], code: &mut Option<Line<'a>>, lines: &mut Vec<Line<'a>>) {
+    for trivia in trivia {
+        match trivia {
+            Trivia::Whitespace(space) => {
+                let newlines = space.matches('\n').count();
+                if newlines > 0 {
+                    lines.extend(code.take());
+                }
+                if newlines > 1 {
+                    lines.push(Line::Blank);
+                }
+            }
+            Trivia::Comment(text) => match code {
+                Some(Line::Code { comment, .. }) => *comment = Some(text.trim_end()),
+                _ => lines.push(Line::Comment(text.trim_end())),
+            },
+        }
+    }
+}
+
+/// The indentation state inside one pair of brackets.
+struct Frame {
+    indent: usize,
+    /// Indentation of the statement the last line belongs to.
+    statement: usize,
+    /// Indentation of the lines holding each `if` whose `else` has not been
+    /// seen yet.
+    ifs: Vec<usize>,
+    /// Whether the last line left its statement unfinished.
+    open: bool,
+}
+
+impl Frame {
+    fn new(indent: usize) -> Self {
+        Self {
+            indent,
+            statement: indent,
+            ifs: Vec::new(),
+            open: false,
+        }
+    }
+}
+
+struct Formatter<'a> {
+    lexemes: &'a [Lexeme<'a>],
+    /// Whether each `|` opens a parameter list rather than closing one.
+    opening: Vec<bool>,
+    /// Whether each `{` and `}` belongs to a hash literal or import list.
+    hash: Vec<bool>,
+    /// Whether each `+` and `-` is a prefix operator.
+    prefix: Vec<bool>,
+    frames: Vec<Frame>,
+    /// Comment and blank lines, indented like the next line of code.
+    pending: Vec<Line<'a>>,
+    out: String,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(lexemes: &'a [Lexeme<'a>]) -> Self {
+        let tokens: Vec<&Token> = lexemes.iter().map(|l| &l.token).collect();
+        let mut opening = vec![false; tokens.len()];
+        let mut hash = vec![false; tokens.len()];
+        let mut prefix = vec![false; tokens.len()];
+        // The opening `|` of each parameter list not yet closed. A `|` closes
+        // the innermost list when it follows an operand or the opening `|`
+        // itself, and otherwise opens a list of its own, as in `b = |x| x|`.
+        let mut params = Vec::new();
+        let mut braces = Vec::new();
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                Token::Pipe(_) => {
+                    let closes = match params.last() {
+                        Some(&open) => open + 1 == i || ends_operand(tokens[i - 1]),
+                        None => false,
+                    };
+                    if closes {
+                        params.pop();
+                    } else {
+                        params.push(i);
+                        opening[i] = true;
+                    }
+                }
+                Token::LBrace(_) => {
+                    hash[i] = is_hash(&tokens, i);
+                    braces.push(hash[i]);
+                }
+                Token::RBrace(_) => hash[i] = braces.pop().unwrap_or(false),
+                Token::Plus(_) | Token::Minus(_) => {
+                    prefix[i] = i == 0 || !ends_operand(tokens[i - 1]);
+                }
+                _ => {}
+            }
+        }
+        Self {
+            lexemes,
+            opening,
+            hash,
+            prefix,
+            frames: vec![Frame::new(0)],
+            pending: Vec::new(),
+            out: String::new(),
+        }
+    }
+
+    fn token(&self, i: usize) -> &Token {
+        &self.lexemes[i].token
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames
+            .last_mut()
+            .expect("the top level frame is never popped")
+    }
+
+    fn line(&mut self, line: Line<'a>) {
+        match line {
+            Line::Code { tokens, comment } => self.code(tokens, comment),
+            line => self.pending.push(line),
+        }
+    }
+
+    fn code(&mut self, tokens: Range<usize>, comment: Option<&str>) {
+        let first = tokens.start;
+        let closes = is_closer(self.token(first)) && self.frames.len() > 1;
+        let branch = matches!(self.token(first), Token::Then(_) | Token::Else(_));
+        let leading = self.leading(first);
+        let frame = self.frame();
+        let indent = if closes {
+            frame.indent - INDENT
+        } else if branch {
+            frame.ifs.last().unwrap_or(&frame.statement) + INDENT
+        } else if frame.open || leading {
+            frame.statement + INDENT
+        } else {
+            frame.statement = frame.indent;
+            frame.ifs.clear();
+            frame.indent
+        };
+        let comments = if closes { indent + INDENT } else { indent };
+        self.flush(comments);
+
+        let mut text = " ".repeat(indent);
+        for i in tokens.clone() {
+            if i > first && self.spaced(i) {
+                text.push(' ');
+            }
+            text.push_str(self.lexemes[i].text);
+            match self.token(i) {
+                Token::If(_) => self.frame().ifs.push(indent),
+                Token::Else(_) => {
+                    self.frame().ifs.pop();
+                }
+                t if is_opener(t) => self.frames.push(Frame::new(indent + INDENT)),
+                t if is_closer(t) && self.frames.len() > 1 => {
+                    self.frames.pop();
+                }
+                _ => {}
+            }
+        }
+        if let Some(comment) = comment {
+            text.push(' ');
+            text.push_str(comment);
+        }
+        self.frame().open = self.dangling(tokens.end - 1);
+        self.out.push_str(&text);
+        self.out.push('\n');
+    }
+
+    /// Writes out pending comments and blank lines, collapsing runs of blank
+    /// lines and dropping them at the start of the file.
+    fn flush(&mut self, indent: usize) {
+        for line in std::mem::take(&mut self.pending) {
+            match line {
+                Line::Comment(text) => {
+                    self.out.push_str(&" ".repeat(indent));
+                    self.out.push_str(text);
+                    self.out.push('\n');
+                }
+                _ if self.out.is_empty() || self.out.ends_with("\n\n") => {}
+                _ => self.out.push('\n'),
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        let indent = self.frame().indent;
+        self.flush(indent);
+        while self.out.ends_with("\n\n") {
+            self.out.pop();
+        }
+        self.out
+    }
+
+    /// Whether to put a space between the lexemes at `i - 1` and `i`.
+    fn spaced(&self, i: usize) -> bool {
+        let (prev, next) = (self.token(i - 1), self.token(i));
+        if self.prefix[i - 1] {
+            return !self.lexemes[i].trivia.is_empty();
+        }
+        let spaced = match (prev, next) {
+            (
+                _,
+                Token::RParenthesis(_)
+                | Token::RBracket(_)
+                | Token::Comma(_)
+                | Token::SemiColon(_)
+                | Token::Colon(_)
+                | Token::Dot(_),
+            ) => false,
+            (_, Token::Pipe(_)) if !self.opening[i] => false,
+            (_, Token::RBrace(_)) if self.hash[i] => false,
+            (
+                Token::LParenthesis(_)
+                | Token::LBracket(_)
+                | Token::Dot(_)
+                | Token::DotDot(_)
+                | Token::At(_)
+                | Token::Not(_),
+                _,
+            ) => false,
+            (Token::Pipe(_), _) if self.opening[i - 1] => false,
+            (Token::LBrace(_), _) if self.hash[i - 1] => false,
+            (
+                Token::Identifier(_)
+                | Token::Str(_)
+                | Token::Template(_)
+                | Token::RParenthesis(_)
+                | Token::RBracket(_),
+                Token::LParenthesis(_) | Token::LBracket(_),
+            ) => false,
+            _ => true,
+        };
+        spaced || self.glues(i)
+    }
+
+    /// Whether writing the two lexemes without a space would lex differently.
+    /// The lexeme after them is kept in place, since `1.` alone lexes as a
+    /// float but `1.add` as `1` and `.add`.
+    fn glues(&self, i: usize) -> bool {
+        let (prev, next) = (&self.lexemes[i - 1], &self.lexemes[i]);
+        let after = self.lexemes.get(i + 1).map_or("", |l| l.text);
+        let rest = format!("{}{}", next.text, after);
+        let joined = format!("{}{}", prev.text, rest);
+        let lexed: IResult<&str, Token> = token(&joined);
+        !matches!(lexed, Ok((r, t)) if r == rest && t == prev.token)
+    }
+
+    /// Whether a line ending with the lexeme at `i` continues on the next.
+    fn dangling(&self, i: usize) -> bool {
+        match self.token(i) {
+            Token::Plus(_) | Token::Minus(_) => true,
+            t => is_operator(t) || is_keyword(t) || matches!(t, Token::Pipe(_) | Token::DotDot(_)),
+        }
+    }
+
+    /// Whether a line starting with the lexeme at `i` continues the last one.
+    fn leading(&self, i: usize) -> bool {
+        match self.token(i) {
+            Token::Plus(_) | Token::Minus(_) => !self.prefix[i],
+            Token::Not(_) => false,
+            t => is_operator(t) || matches!(t, Token::ImportFrom(_)),
+        }
+    }
+}
+
+/// Tells a hash literal or import list from a block by what follows `{`.
+fn is_hash(tokens: &[&Token], i: usize) -> bool {
+    if i > 0 && matches!(tokens[i - 1], Token::Import(_)) {
+        return true;
+    }
+    let colon = |j: usize| matches!(tokens.get(j), Some(Token::Colon(_)));
+    match tokens.get(i + 1) {
+        Some(Token::RBrace(_)) => true,
+        Some(Token::Str(_) | Token::Template(_) | Token::Int(_) | Token::Bool(_)) => colon(i + 2),
+        Some(Token::Identifier(_)) => {
+            matches!(tokens.get(i + 2), Some(Token::Colon(_) | Token::Comma(_)))
+        }
+        Some(Token::LBracket(_)) => {
+            let mut depth = 0;
+            for (j, token) in tokens.iter().enumerate().skip(i + 1) {
+                match token {
+                    Token::LBracket(_) => depth += 1,
+                    Token::RBracket(_) if depth == 1 => return colon(j + 1),
+                    Token::RBracket(_) => depth -= 1,
+                    _ => {}
+                }
+            }
+            false
+        }
+        _ => false,
+    }
+}
+
+fn ends_operand(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Identifier(_)
+            | Token::Str(_)
+            | Token::Template(_)
+            | Token::Int(_)
+            | Token::Float(_)
+            | Token::Bool(_)
+            | Token::RParenthesis(_)
+            | Token::RBracket(_)
+            | Token::RBrace(_)
+    )
+}
+
+fn is_opener(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::LParenthesis(_) | Token::LBracket(_) | Token::LBrace(_)
+    )
+}
+
+fn is_closer(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::RParenthesis(_) | Token::RBracket(_) | Token::RBrace(_)
+    )
+}
+
+/// Operators other than `+` and `-`, which can also be prefixes.
+fn is_operator(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Equal(_)
+            | Token::NotEqual(_)
+            | Token::Assign(_)
+            | Token::Multiply(_)
+            | Token::Divide(_)
+            | Token::Rem(_)
+            | Token::Not(_)
+            | Token::GreaterThanEqual(_)
+            | Token::LessThanEqual(_)
+            | Token::GreaterThan(_)
+            | Token::LessThan(_)
+            | Token::Pipeline(_)
+            | Token::ComposeRight(_)
+            | Token::ComposeLeft(_)
+            | Token::Dollar(_)
+            | Token::Colon(_)
+            | Token::Dot(_)
+    )
+}
+
+fn is_keyword(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Return(_)
+            | Token::If(_)
+            | Token::Then(_)
+            | Token::Else(_)
+            | Token::Import(_)
+            | Token::ImportFrom(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn assert_format(source: &str, expected: &str) {
+        let formatted = format(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format(&formatted).unwrap(), formatted);
+        assert_eq!(program(&formatted), program(source));
+    }
+
+    #[test]
+    fn spacing_test() {
+        assert_format("x=1+-2*( 3-4 )", "x = 1 + -2 * (3 - 4)\n");
+        assert_format("f = |a,b|a+b ;f (1)( 2 )", "f = |a, b| a + b; f(1)(2)\n");
+        assert_format("h={ \"a\" :1,[k]:[ ] }", "h = {\"a\": 1, [k]: []}\n");
+        assert_format("{a;b}", "{ a; b }\n");
+        assert_format("{ name }", "{ name }\n");
+        assert_format("{ name, }", "{name,}\n");
+        assert_format("{ a : 1, b }", "{a: 1, b}\n");
+        assert_format("xs . map( ( * 2 ) )[0]", "xs.map((* 2))[0]\n");
+        assert_format("g = (- 1) $ !x", "g = (- 1) $ !x\n");
+        assert_format("@ memo\nf = |..xs| [ ..xs ]", "@memo\nf = |..xs| [..xs]\n");
+        assert_format(
+            "f = |a = 1, b = |x| x| b(a)",
+            "f = |a = 1, b = |x| x| b(a)\n",
+        );
+        assert_format("g = ||1; h = |a| |b| a", "g = || 1; h = |a| |b| a\n");
+        assert_format("1.add(2); 1 . add(2)", "1.add(2); 1.add(2)\n");
+        assert_format("x = 1. ; y = - -1", "x = 1.; y = - -1\n");
+        assert_format(
+            "import { a,b } from \"lib.kn\"",
+            "import {a, b} from \"lib.kn\"\n",
+        );
+    }
+
+    #[test]
+    fn indent_test() {
+        assert_format(
+            "f = |x|\nif (x)\nthen {\ny = x;\ny\n}\nelse 0\n",
+            "f = |x|\n    if (x)\n        then {\n            y = x;\n            y\n        }\n        else 0\n",
+        );
+        assert_format(
+            "xs\n.map(f)\n|> print\nys = [\n  1,\n      2\n  ]\n",
+            "xs\n    .map(f)\n    |> print\nys = [\n    1,\n    2\n]\n",
+        );
+    }
+
+    #[test]
+    fn comment_test() {
+        assert_format(
+            "\n\n// head  \nx = 1   // one\n\n\n\ny = {\n// inside\n  2\n    // last\n}\n// end\n\n",
+            "// head\nx = 1 // one\n\ny = {\n    // inside\n    2\n    // last\n}\n// end\n",
+        );
+        assert_format(
+            "#!/usr/bin/env kani  \nprint( 1 )",
+            "#!/usr/bin/env kani\nprint(1)\n",
+        );
+        assert_format("", "");
+    }
+
+    #[test]
+    fn error_test() {
+        assert!(format("x = ").is_err());
+        assert!(format("x = 1 )").is_err());
+    }
+
+    fn examples(dir: &Path, files: &mut Vec<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                examples(&path, files);
+            } else if path.extension().is_some_and(|e| e == "kn") {
+                files.push(fs::read_to_string(path).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn examples_test() {
+        let mut files = Vec::new();
+        examples(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples"),
+            &mut files,
+        );
+        assert!(!files.is_empty());
+        for source in files {
+            assert_format(&source, &source);
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use super::Identifier;
+use crate::error::IResult;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::{alpha1, alphanumeric1};
 use nom::combinator::{map, recognize};
 use nom::multi::many0;
 use nom::sequence::pair;
-use nom::IResult;
 
 pub fn identifier(input: &str) -> IResult<&str, Identifier> {
     map(
@@ -1,7 +1,7 @@
 use super::Illegal;
+use crate::error::IResult;
 use nom::bytes::complete::take;
 use nom::combinator::value;
-use nom::IResult;
 
 pub fn illegal(input: &str) -> IResult<&str, Illegal> {
     value(Illegal, take(1usize))(input)
@@ -5,38 +5,80 @@ mod operator;
 mod punctuation;
 mod reserved;
 mod string;
+mod trivia;
 
+use crate::error::IResult;
 use crate::token::*;
 use identifier::*;
 use illegal::*;
 use nom::branch::alt;
-use nom::character::complete::multispace0;
-use nom::combinator::into;
-use nom::sequence::delimited;
-use nom::IResult;
+use nom::bytes::complete::tag;
+use nom::character::complete::{line_ending, not_line_ending};
+use nom::combinator::{all_consuming, consumed, into, map, opt, recognize};
+use nom::multi::many0;
+use nom::sequence::{delimited, pair, tuple};
 use number::*;
 use operator::*;
 use punctuation::*;
 use reserved::*;
 use string::*;
+pub use trivia::Trivia;
+use trivia::*;
+
+/// A token together with the exact text it was lexed from and the trivia
+/// before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lexeme<'a> {
+    pub trivia: Vec<Trivia<'a>>,
+    pub token: Token,
+    pub text: &'a str,
+}
 
 pub fn token(input: &str) -> IResult<&str, Token> {
-    delimited(
-        multispace0,
-        alt((
-            operator_token,
-            punctuation_token,
-            string_token,
-            reserved_token,
-            identifier_token,
-            float_token,
-            integer_token,
-            illegal_token,
-        )),
-        multispace0,
+    delimited(skip, bare_token, skip)(input)
+}
+
+/// Skips whitespace and comments.
+pub fn skip(input: &str) -> IResult<&str, &str> {
+    recognize(trivia)(input)
+}
+
+pub fn lexeme(input: &str) -> IResult<&str, Lexeme<'_>> {
+    map(
+        pair(trivia, consumed(bare_token)),
+        |(trivia, (text, token))| Lexeme {
+            trivia,
+            token,
+            text,
+        },
     )(input)
 }
 
+/// Lexes the whole input without losing anything: concatenating the trivia
+/// and text of every lexeme, then the trailing trivia, gives back the input.
+pub fn lexemes(input: &str) -> IResult<&str, (Vec<Lexeme<'_>>, Vec<Trivia<'_>>)> {
+    all_consuming(pair(many0(lexeme), trivia))(input)
+}
+
+fn bare_token(input: &str) -> IResult<&str, Token> {
+    alt((
+        operator_token,
+        punctuation_token,
+        string_token,
+        reserved_token,
+        identifier_token,
+        float_token,
+        integer_token,
+        illegal_token,
+    ))(input)
+}
+
+/// Consumes a `#!` line at the very start of a file, so that scripts can be
+/// made executable.
+pub fn shebang(input: &str) -> IResult<&str, &str> {
+    recognize(tuple((tag("#!"), not_line_ending, opt(line_ending))))(input)
+}
+
 fn operator_token(input: &str) -> IResult<&str, Token> {
     alt((
         into(equal),
@@ -48,10 +90,13 @@ fn operator_token(input: &str) -> IResult<&str, Token> {
         into(divide),
         into(rem),
         into(not),
+        into(compose_right),
+        into(compose_left),
         into(greater_than_equal),
         into(less_than_equal),
         into(greater_than),
         into(less_than),
+        into(pipeline),
         into(at),
         into(dollar),
     ))(input)
@@ -62,6 +107,7 @@ fn punctuation_token(input: &str) -> IResult<&str, Token> {
         into(comma),
         into(semi_colon),
         into(colon),
+        into(dot_dot),
         into(dot),
         into(pipe),
         into(lparen),
@@ -74,7 +120,13 @@ fn punctuation_token(input: &str) -> IResult<&str, Token> {
 }
 
 fn string_token(input: &str) -> IResult<&str, Token> {
-    into(string)(input)
+    alt((
+        into(raw_string),
+        into(multiline_string),
+        into(string),
+        into(template),
+        into(character),
+    ))(input)
 }
 
 fn reserved_token(input: &str) -> IResult<&str, Token> {
@@ -83,6 +135,8 @@ fn reserved_token(input: &str) -> IResult<&str, Token> {
         into(if_),
         into(then),
         into(else_),
+        into(import),
+        into(from),
         into(true_),
         into(false_),
     ))(input)
@@ -121,6 +175,43 @@ mod tests {
         assert_eq!(token("#"), Ok(("", Illegal.into())));
     }
 
+    #[test]
+    fn shebang_test() {
+        assert_eq!(
+            shebang("#!/usr/bin/env kani\nprint(1)"),
+            Ok(("print(1)", "#!/usr/bin/env kani\n"))
+        );
+        assert_eq!(shebang("#!kani"), Ok(("", "#!kani")));
+        assert!(shebang(" #!kani").is_err());
+    }
+
+    #[test]
+    fn comment_test() {
+        assert_eq!(token("// note\n  1 // one"), Ok(("", 1.into())));
+        assert_eq!(token("/ 1"), Ok(("1", Divide.into())));
+    }
+
+    #[test]
+    fn lexemes_test() {
+        let code = "// add\nadd = |a, b| a + b // sum\n";
+        let (_, (lexemes, trailing)) = lexemes(code).unwrap();
+        assert_eq!(
+            lexemes[0],
+            Lexeme {
+                trivia: vec![Trivia::Comment("// add"), Trivia::Whitespace("\n")],
+                token: Identifier("add".into()).into(),
+                text: "add",
+            }
+        );
+        assert_eq!(lexemes.len(), 10);
+        let text: String = lexemes
+            .iter()
+            .flat_map(|l| l.trivia.iter().map(Trivia::text).chain(Some(l.text)))
+            .chain(trailing.iter().map(Trivia::text))
+            .collect();
+        assert_eq!(text, code);
+    }
+
     #[test]
     fn integer_test() {
         assert_eq!(token("12_34"), Ok(("", 1234.into())));
@@ -134,6 +225,8 @@ mod tests {
         assert_eq!(token("12_34.56_78"), Ok(("", 1234.5678.into())));
         assert_eq!(token("1234.56e+78"), Ok(("", 1234.56e+78.into())));
         assert_eq!(token("1234.56e-78"), Ok(("", 1234.56e-78.into())));
+        assert_eq!(token("12."), Ok(("", 12.0.into())));
+        assert_eq!(token("12.abs"), Ok((".abs", 12.into())));
     }
 
     #[test]
@@ -151,6 +244,9 @@ mod tests {
         assert_eq!(token("<="), Ok(("", LessThanEqual.into())));
         assert_eq!(token(">"), Ok(("", GreaterThan.into())));
         assert_eq!(token("<"), Ok(("", LessThan.into())));
+        assert_eq!(token("|>"), Ok(("", Pipeline.into())));
+        assert_eq!(token(">>"), Ok(("", ComposeRight.into())));
+        assert_eq!(token("<<"), Ok(("", ComposeLeft.into())));
         assert_eq!(token("@"), Ok(("", At.into())));
         assert_eq!(token("$"), Ok(("", Dollar.into())));
     }
@@ -160,6 +256,7 @@ mod tests {
         assert_eq!(token(","), Ok(("", Comma.into())));
         assert_eq!(token(";"), Ok(("", SemiColon.into())));
         assert_eq!(token(":"), Ok(("", Colon.into())));
+        assert_eq!(token(".."), Ok(("", DotDot.into())));
         assert_eq!(token("."), Ok(("", Dot.into())));
         assert_eq!(token("|"), Ok(("", Pipe.into())));
         assert_eq!(token("("), Ok(("", LParenthesis.into())));
@@ -176,6 +273,12 @@ mod tests {
         assert_eq!(token("if"), Ok(("", If.into())));
         assert_eq!(token("then"), Ok(("", Then.into())));
         assert_eq!(token("else"), Ok(("", Else.into())));
+        assert_eq!(token("import"), Ok(("", Import.into())));
+        assert_eq!(token("from"), Ok(("", ImportFrom.into())));
+        assert_eq!(
+            token("from_entries"),
+            Ok(("", Identifier("from_entries".into()).into()))
+        );
         assert_eq!(token("true"), Ok(("", true.into())));
         assert_eq!(token("false"), Ok(("", false.into())));
     }
@@ -185,5 +288,12 @@ mod tests {
             token("\"test\\n\\r\\t\\b\\f\\\\\\/\\\"\\u{0A}\""),
             Ok(("", "test\n\r\t\u{08}\u{0C}\\/\"\u{0A}".to_string().into()))
         );
+        assert_eq!(token("r\"\\d\""), Ok(("", "\\d".to_string().into())));
+        assert_eq!(
+            token("\"\"\"\n  a\n  \"\"\""),
+            Ok(("", "a".to_string().into()))
+        );
+        assert_eq!(token("'a'"), Ok(("", "a".to_string().into())));
+        assert_eq!(token("r"), Ok(("", Identifier("r".into()).into())));
     }
 }
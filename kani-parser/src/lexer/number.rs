@@ -1,11 +1,11 @@
 use crate::alias::{Float, Int};
+use crate::error::IResult;
 use nom::branch::alt;
 use nom::bytes::complete::tag_no_case;
-use nom::character::complete::{char, digit1, hex_digit1, oct_digit1, one_of};
-use nom::combinator::{map_res, opt, recognize};
+use nom::character::complete::{char, digit1, hex_digit1, oct_digit1, one_of, satisfy};
+use nom::combinator::{map_res, not, opt, recognize};
 use nom::multi::{many0, many1};
 use nom::sequence::{preceded, terminated, tuple};
-use nom::IResult;
 
 pub fn integer(input: &str) -> IResult<&str, Int> {
     alt((hexadecimal, octal, binary, decimal))(input)
@@ -74,6 +74,14 @@ fn float_str(input: &str) -> IResult<&str, &str> {
             opt(one_of("+-")),
             decimal_str,
         ))),
-        recognize(tuple((decimal_str, char('.'), opt(decimal_str)))),
+        recognize(tuple((
+            decimal_str,
+            char('.'),
+            alt((decimal_str, not_identifier)),
+        ))),
     ))(input)
 }
+
+fn not_identifier(input: &str) -> IResult<&str, &str> {
+    recognize(not(satisfy(|c| c.is_alphabetic() || c == '_')))(input)
+}
@@ -1,10 +1,10 @@
 use super::{
-    Assign, At, Divide, Dollar, Equal, GreaterThan, GreaterThanEqual, LessThan, LessThanEqual,
-    Minus, Multiply, Not, NotEqual, Plus, Rem,
+    Assign, At, ComposeLeft, ComposeRight, Divide, Dollar, Equal, GreaterThan, GreaterThanEqual,
+    LessThan, LessThanEqual, Minus, Multiply, Not, NotEqual, Pipeline, Plus, Rem,
 };
+use crate::error::IResult;
 use nom::bytes::complete::tag;
 use nom::combinator::value;
-use nom::IResult;
 
 pub fn equal(input: &str) -> IResult<&str, Equal> {
     value(Equal, tag("=="))(input)
@@ -58,6 +58,18 @@ pub fn less_than(input: &str) -> IResult<&str, LessThan> {
     value(LessThan, tag("<"))(input)
 }
 
+pub fn pipeline(input: &str) -> IResult<&str, Pipeline> {
+    value(Pipeline, tag("|>"))(input)
+}
+
+pub fn compose_right(input: &str) -> IResult<&str, ComposeRight> {
+    value(ComposeRight, tag(">>"))(input)
+}
+
+pub fn compose_left(input: &str) -> IResult<&str, ComposeLeft> {
+    value(ComposeLeft, tag("<<"))(input)
+}
+
 pub fn at(input: &str) -> IResult<&str, At> {
     value(At, tag("@"))(input)
 }
@@ -85,6 +97,9 @@ mod tests {
         assert_eq!(less_than_equal("<=;"), Ok((";", LessThanEqual)));
         assert_eq!(greater_than(">;"), Ok((";", GreaterThan)));
         assert_eq!(less_than("<;"), Ok((";", LessThan)));
+        assert_eq!(pipeline("|>;"), Ok((";", Pipeline)));
+        assert_eq!(compose_right(">>;"), Ok((";", ComposeRight)));
+        assert_eq!(compose_left("<<;"), Ok((";", ComposeLeft)));
         assert_eq!(at("@;"), Ok((";", At)));
         assert_eq!(dollar("$;"), Ok((";", Dollar)));
     }
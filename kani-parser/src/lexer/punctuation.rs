@@ -1,10 +1,10 @@
 use super::{
-    Colon, Comma, Dot, LBrace, LBracket, LParenthesis, Pipe, RBrace, RBracket, RParenthesis,
-    SemiColon,
+    Colon, Comma, Dot, DotDot, LBrace, LBracket, LParenthesis, Pipe, RBrace, RBracket,
+    RParenthesis, SemiColon,
 };
+use crate::error::IResult;
 use nom::bytes::complete::tag;
 use nom::combinator::value;
-use nom::IResult;
 
 pub fn comma(input: &str) -> IResult<&str, Comma> {
     value(Comma, tag(","))(input)
@@ -18,6 +18,10 @@ pub fn colon(input: &str) -> IResult<&str, Colon> {
     value(Colon, tag(":"))(input)
 }
 
+pub fn dot_dot(input: &str) -> IResult<&str, DotDot> {
+    value(DotDot, tag(".."))(input)
+}
+
 pub fn dot(input: &str) -> IResult<&str, Dot> {
     value(Dot, tag("."))(input)
 }
@@ -59,6 +63,7 @@ mod tests {
         assert_eq!(comma(",;"), Ok((";", Comma)));
         assert_eq!(semi_colon(";;"), Ok((";", SemiColon)));
         assert_eq!(colon(":;"), Ok((";", Colon)));
+        assert_eq!(dot_dot("..;"), Ok((";", DotDot)));
         assert_eq!(dot(".;"), Ok((";", Dot)));
         assert_eq!(pipe("|;"), Ok((";", Pipe)));
         assert_eq!(lparen("(;"), Ok((";", LParenthesis)));
@@ -1,31 +1,45 @@
-use super::{Else, If, Return, Then};
+use super::{Else, If, Import, ImportFrom, Return, Then};
 use crate::alias::Bool;
+use crate::error::IResult;
 use nom::bytes::complete::tag;
-use nom::combinator::value;
-use nom::IResult;
+use nom::character::complete::satisfy;
+use nom::combinator::{not, value};
+use nom::sequence::terminated;
+
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    terminated(tag(word), not(satisfy(|c| c.is_alphanumeric() || c == '_')))
+}
 
 pub fn return_(input: &str) -> IResult<&str, Return> {
-    value(Return, tag("return"))(input)
+    value(Return, keyword("return"))(input)
 }
 
 pub fn if_(input: &str) -> IResult<&str, If> {
-    value(If, tag("if"))(input)
+    value(If, keyword("if"))(input)
 }
 
 pub fn then(input: &str) -> IResult<&str, Then> {
-    value(Then, tag("then"))(input)
+    value(Then, keyword("then"))(input)
 }
 
 pub fn else_(input: &str) -> IResult<&str, Else> {
-    value(Else, tag("else"))(input)
+    value(Else, keyword("else"))(input)
+}
+
+pub fn import(input: &str) -> IResult<&str, Import> {
+    value(Import, keyword("import"))(input)
+}
+
+pub fn from(input: &str) -> IResult<&str, ImportFrom> {
+    value(ImportFrom, keyword("from"))(input)
 }
 
 pub fn true_(input: &str) -> IResult<&str, Bool> {
-    value(true, tag("true"))(input)
+    value(true, keyword("true"))(input)
 }
 
 pub fn false_(input: &str) -> IResult<&str, Bool> {
-    value(false, tag("false"))(input)
+    value(false, keyword("false"))(input)
 }
 
 #[cfg(test)]
@@ -38,7 +52,16 @@ mod tests {
         assert_eq!(if_("if;"), Ok((";", If)));
         assert_eq!(then("then;"), Ok((";", Then)));
         assert_eq!(else_("else;"), Ok((";", Else)));
+        assert_eq!(import("import;"), Ok((";", Import)));
+        assert_eq!(from("from;"), Ok((";", ImportFrom)));
         assert_eq!(true_("true;"), Ok((";", true)));
         assert_eq!(false_("false;"), Ok((";", false)));
     }
+
+    #[test]
+    fn boundary_test() {
+        assert!(if_("iffy").is_err());
+        assert!(from("from_entries").is_err());
+        assert!(true_("true1").is_err());
+    }
 }
@@ -1,28 +1,114 @@
+use super::{token, Template, TemplatePart, Token};
+use crate::error::IResult;
 use nom::branch::alt;
-use nom::bytes::complete::{is_not, take_while_m_n};
-use nom::character::complete::{char, multispace1};
-use nom::combinator::{map, map_opt, map_res, value, verify};
-use nom::multi::fold_many0;
-use nom::sequence::{delimited, preceded};
-use nom::IResult;
+use nom::bytes::complete::{is_not, tag, take_until, take_while_m_n};
+use nom::character::complete::{char, multispace0, multispace1, none_of};
+use nom::combinator::{map, map_opt, map_res, not, recognize, value, verify};
+use nom::multi::{fold_many0, many0_count};
+use nom::sequence::{delimited, preceded, terminated};
 
 pub fn string(input: &str) -> IResult<&str, String> {
-    let build_string = fold_many0(fragment, String::new(), |mut string, fragment| {
+    map_opt(template, |Template(parts)| match parts.as_slice() {
+        [] => Some(String::new()),
+        [TemplatePart::Str(s)] => Some(s.clone()),
+        _ => None,
+    })(input)
+}
+
+pub fn raw_string(input: &str) -> IResult<&str, String> {
+    let (input, hashes) =
+        preceded(char('r'), terminated(many0_count(char('#')), char('"')))(input)?;
+    let closing = format!("\"{}", "#".repeat(hashes));
+    let (input, s) = terminated(take_until(closing.as_str()), tag(closing.as_str()))(input)?;
+    Ok((input, s.to_string()))
+}
+
+/// Triple-quoted strings are raw. A leading newline, a trailing whitespace-only
+/// line and the indentation shared by all non-blank lines are removed.
+pub fn multiline_string(input: &str) -> IResult<&str, String> {
+    map(
+        delimited(tag("\"\"\""), take_until("\"\"\""), tag("\"\"\"")),
+        dedent,
+    )(input)
+}
+
+pub fn character(input: &str) -> IResult<&str, String> {
+    map(
+        delimited(char('\''), alt((escaped_char, none_of("'\\"))), char('\'')),
+        String::from,
+    )(input)
+}
+
+fn dedent(s: &str) -> String {
+    let s = s
+        .strip_prefix("\r\n")
+        .or_else(|| s.strip_prefix('\n'))
+        .unwrap_or(s);
+    let mut lines: Vec<&str> = s.split('\n').map(|l| l.trim_end_matches('\r')).collect();
+    if lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+    let indent = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.chars().take_while(|c| c.is_whitespace()).count())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|l| l.chars().skip(indent).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn template(input: &str) -> IResult<&str, Template> {
+    let build_parts = fold_many0(fragment, Vec::new(), |mut parts, fragment| {
         match fragment {
-            StringFragment::Literal(s) => string.push_str(s),
-            StringFragment::EscapedChar(c) => string.push(c),
+            StringFragment::Literal(s) => push_str(&mut parts, s),
+            StringFragment::EscapedChar(c) => push_str(&mut parts, c.encode_utf8(&mut [0; 4])),
             StringFragment::EscapedWS => {}
+            StringFragment::Interpolation(code) => parts.push(TemplatePart::Code(code.to_string())),
         }
-        string
+        parts
     });
-    delimited(char('"'), build_string, char('"'))(input)
+    map(delimited(char('"'), build_parts, char('"')), Template)(input)
+}
+
+fn push_str(parts: &mut Vec<TemplatePart>, s: &str) {
+    match parts.last_mut() {
+        Some(TemplatePart::Str(last)) => last.push_str(s),
+        _ => parts.push(TemplatePart::Str(s.to_string())),
+    }
+}
+
+fn interpolation(input: &str) -> IResult<&str, &str> {
+    delimited(tag("${"), code, preceded(multispace0, char('}')))(input)
+}
+
+/// Consumes tokens up to the `}` that closes the interpolation, so braces and
+/// quotes inside nested blocks or strings do not end it early.
+fn code(input: &str) -> IResult<&str, &str> {
+    let mut depth = 0;
+    let mut rest = input;
+    loop {
+        let (next, t) = token(rest)?;
+        match t {
+            Token::LBrace(_) => depth += 1,
+            Token::RBrace(_) if depth == 0 => {
+                return Ok((rest, input[..input.len() - rest.len()].trim()));
+            }
+            Token::RBrace(_) => depth -= 1,
+            _ => {}
+        }
+        rest = next;
+    }
 }
 
 fn unicode(input: &str) -> IResult<&str, char> {
     let parse_hex = take_while_m_n(1, 6, |c: char| c.is_ascii_hexdigit());
     let parse_delimited_hex = preceded(char('u'), delimited(char('{'), parse_hex, char('}')));
     let parse_u32 = map_res(parse_delimited_hex, move |hex| u32::from_str_radix(hex, 16));
-    map_opt(parse_u32, |value| std::char::from_u32(value))(input)
+    map_opt(parse_u32, std::char::from_u32)(input)
 }
 
 fn escaped_char(input: &str) -> IResult<&str, char> {
@@ -38,6 +124,8 @@ fn escaped_char(input: &str) -> IResult<&str, char> {
             value('\\', char('\\')),
             value('/', char('/')),
             value('"', char('"')),
+            value('\'', char('\'')),
+            value('$', char('$')),
         )),
     )(input)
 }
@@ -47,8 +135,12 @@ fn escaped_whitespace(input: &str) -> IResult<&str, &str> {
 }
 
 fn literal(input: &str) -> IResult<&str, &str> {
-    let not_quote_slash = is_not("\"\\");
-    verify(not_quote_slash, |s: &str| !s.is_empty())(input)
+    let not_quote_slash_dollar = is_not("\"\\$");
+    let dollar = recognize(terminated(char('$'), not(char('{'))));
+    alt((
+        verify(not_quote_slash_dollar, |s: &str| !s.is_empty()),
+        dollar,
+    ))(input)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -56,13 +148,15 @@ enum StringFragment<'a> {
     Literal(&'a str),
     EscapedChar(char),
     EscapedWS,
+    Interpolation(&'a str),
 }
 
-fn fragment(input: &str) -> IResult<&str, StringFragment> {
+fn fragment(input: &str) -> IResult<&str, StringFragment<'_>> {
     alt((
         map(literal, StringFragment::Literal),
         map(escaped_char, StringFragment::EscapedChar),
         value(StringFragment::EscapedWS, escaped_whitespace),
+        map(interpolation, StringFragment::Interpolation),
     ))(input)
 }
 
@@ -76,5 +170,74 @@ mod tests {
             string("\"test\\n\\r\\t\\b\\f\\\\\\/\\\"\\u{0A}\""),
             Ok(("", "test\n\r\t\u{08}\u{0C}\\/\"\u{0A}".to_string()))
         );
+        assert_eq!(string("\"$1 \\${x}\""), Ok(("", "$1 ${x}".to_string())));
+        assert!(string("\"${x}\"").is_err());
+    }
+
+    #[test]
+    fn raw_string_test() {
+        assert_eq!(
+            raw_string("r\"\\d+\\.${x}\""),
+            Ok(("", "\\d+\\.${x}".to_string()))
+        );
+        assert_eq!(
+            raw_string("r#\"say \"hi\"\"#;"),
+            Ok((";", "say \"hi\"".to_string()))
+        );
+        assert_eq!(
+            raw_string("r##\"a \"# b\"##"),
+            Ok(("", "a \"# b".to_string()))
+        );
+    }
+
+    #[test]
+    fn multiline_string_test() {
+        assert_eq!(
+            multiline_string("\"\"\"\n    SELECT *\n      FROM \"t\"\n    \"\"\""),
+            Ok(("", "SELECT *\n  FROM \"t\"".to_string()))
+        );
+        assert_eq!(
+            multiline_string("\"\"\"\n  a\n\n  b\n\"\"\""),
+            Ok(("", "a\n\nb".to_string()))
+        );
+        assert_eq!(
+            multiline_string("\"\"\"one \\n line\"\"\""),
+            Ok(("", "one \\n line".to_string()))
+        );
+    }
+
+    #[test]
+    fn character_test() {
+        assert_eq!(character("'a'"), Ok(("", "a".to_string())));
+        assert_eq!(character("'蟹'"), Ok(("", "蟹".to_string())));
+        assert_eq!(character("'\\''"), Ok(("", "'".to_string())));
+        assert_eq!(character("'\\n'"), Ok(("", "\n".to_string())));
+        assert!(character("'ab'").is_err());
+    }
+
+    #[test]
+    fn template_test() {
+        assert_eq!(
+            template("\"Hello ${name}, you are ${ age + 1 }\""),
+            Ok((
+                "",
+                Template(vec![
+                    TemplatePart::Str("Hello ".to_string()),
+                    TemplatePart::Code("name".to_string()),
+                    TemplatePart::Str(", you are ".to_string()),
+                    TemplatePart::Code("age + 1".to_string()),
+                ])
+            ))
+        );
+        assert_eq!(
+            template("\"${join(\"}\", {xs})}!\""),
+            Ok((
+                "",
+                Template(vec![
+                    TemplatePart::Code("join(\"}\", {xs})".to_string()),
+                    TemplatePart::Str("!".to_string()),
+                ])
+            ))
+        );
     }
 }
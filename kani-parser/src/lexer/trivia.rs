@@ -0,0 +1,57 @@
+use crate::error::IResult;
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{multispace1, not_line_ending};
+use nom::combinator::{map, recognize};
+use nom::multi::many0;
+use nom::sequence::pair;
+
+/// Source text between tokens that the parser ignores.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trivia<'a> {
+    Whitespace(&'a str),
+    Comment(&'a str),
+}
+
+impl<'a> Trivia<'a> {
+    pub fn text(&self) -> &'a str {
+        match self {
+            Self::Whitespace(text) | Self::Comment(text) => text,
+        }
+    }
+}
+
+pub fn trivia(input: &str) -> IResult<&str, Vec<Trivia<'_>>> {
+    many0(alt((
+        map(multispace1, Trivia::Whitespace),
+        map(comment, Trivia::Comment),
+    )))(input)
+}
+
+/// A `//` comment, up to but not including the line break.
+pub fn comment(input: &str) -> IResult<&str, &str> {
+    recognize(pair(tag("//"), not_line_ending))(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trivia_test() {
+        assert_eq!(
+            trivia("  // one\n//two\nx"),
+            Ok((
+                "x",
+                vec![
+                    Trivia::Whitespace("  "),
+                    Trivia::Comment("// one"),
+                    Trivia::Whitespace("\n"),
+                    Trivia::Comment("//two"),
+                    Trivia::Whitespace("\n"),
+                ]
+            ))
+        );
+        assert_eq!(trivia("/ 2"), Ok(("/ 2", vec![])));
+    }
+}
@@ -1,5 +1,7 @@
 mod alias;
 pub mod ast;
+pub mod error;
+pub mod format;
 pub mod lexer;
 mod macros;
 pub mod parser;
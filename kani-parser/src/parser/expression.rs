@@ -1,12 +1,12 @@
 use crate::ast::*;
+use crate::error::IResult;
 use crate::lexer::token;
-use crate::token::Token;
+use crate::token::{self, Token};
 use crate::verify_token;
 use nom::branch::alt;
-use nom::combinator::{into, map, map_opt, opt};
-use nom::multi::{many0, separated_list0};
-use nom::sequence::{delimited, preceded, separated_pair, terminated, tuple};
-use nom::IResult;
+use nom::combinator::{all_consuming, cut, into, map, map_opt, opt, peek, verify};
+use nom::multi::{many0, separated_list0, separated_list1};
+use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
 
 pub fn expression(input: &str) -> IResult<&str, Expression> {
     pratt(Precedence::Lowest)(input)
@@ -16,8 +16,18 @@ pub fn root(input: &str) -> IResult<&str, Expression> {
     terminated(expression, opt(verify_token!(Token::SemiColon(_))))(input)
 }
 
-fn expressions(input: &str) -> IResult<&str, Vec<Expression>> {
-    separated_list0(verify_token!(Token::Comma(_)), expression)(input)
+fn elements(input: &str) -> IResult<&str, Vec<Expression>> {
+    separated_list0(verify_token!(Token::Comma(_)), element)(input)
+}
+
+fn element(input: &str) -> IResult<&str, Expression> {
+    alt((into(spread), expression))(input)
+}
+
+fn spread(input: &str) -> IResult<&str, Spread> {
+    map(preceded(verify_token!(Token::DotDot(_)), expression), |x| {
+        Spread(Box::new(x))
+    })(input)
 }
 
 fn pratt(precedence: Precedence) -> impl FnMut(&str) -> IResult<&str, Expression> {
@@ -34,7 +44,7 @@ fn pratt_to_peek(
 ) -> IResult<&str, Expression> {
     if left < Precedence::Call {
         if let Ok((input, arguments)) = arguments(input) {
-            let expression = Postfix::currying(&arguments, expression);
+            let expression = Postfix::calling(arguments, expression);
             return pratt_to_peek(input, left, expression.into());
         }
     }
@@ -43,6 +53,15 @@ fn pratt_to_peek(
             let expression = Postfix::new(PostfixOperator::Index(index), expression);
             return pratt_to_peek(input, left, expression.into());
         }
+        if let Ok((input, Field(method))) = field(input) {
+            if let Ok((input, arguments)) = arguments(input) {
+                let method = Method::new(method, arguments);
+                let expression = Postfix::new(method.into(), expression);
+                return pratt_to_peek(input, left, expression.into());
+            }
+            let expression = Postfix::new(PostfixOperator::Field(Field(method)), expression);
+            return pratt_to_peek(input, left, expression.into());
+        }
     }
     match infix_operator(input) {
         Ok((input, right)) if left < right.0 => {
@@ -57,7 +76,9 @@ fn atom(input: &str) -> IResult<&str, Expression> {
     alt((
         into(assign),
         into(return_),
+        into(import),
         into(literal),
+        into(template),
         into(identifier),
         into(prefix),
         into(parenthesis),
@@ -69,14 +90,26 @@ fn atom(input: &str) -> IResult<&str, Expression> {
     ))(input)
 }
 
-fn arguments(input: &str) -> IResult<&str, Vec<Expression>> {
+fn arguments(input: &str) -> IResult<&str, Vec<PostfixOperator>> {
     delimited(
         verify_token!(Token::LParenthesis(_)),
-        expressions,
+        separated_list0(verify_token!(Token::Comma(_)), argument),
         verify_token!(Token::RParenthesis(_)),
     )(input)
 }
 
+fn argument(input: &str) -> IResult<&str, PostfixOperator> {
+    alt((
+        map(
+            separated_pair(identifier, verify_token!(Token::Colon(_)), expression),
+            |(name, expression)| NamedArgument::new(name, expression).into(),
+        ),
+        map(element, |expression| {
+            Argument(Some(Box::new(expression))).into()
+        }),
+    ))(input)
+}
+
 fn index(input: &str) -> IResult<&str, Index> {
     map(
         delimited(
@@ -88,6 +121,10 @@ fn index(input: &str) -> IResult<&str, Index> {
     )(input)
 }
 
+fn field(input: &str) -> IResult<&str, Field> {
+    map(preceded(verify_token!(Token::Dot(_)), identifier), Field)(input)
+}
+
 fn infix_operator(input: &str) -> IResult<&str, (Precedence, InfixOperator)> {
     map_opt(token, |t| match t {
         Token::Equal(_) => Some((Precedence::Equals, InfixOperator::Equal)),
@@ -103,6 +140,10 @@ fn infix_operator(input: &str) -> IResult<&str, (Precedence, InfixOperator)> {
         Token::Multiply(_) => Some((Precedence::Product, InfixOperator::Multiply)),
         Token::Divide(_) => Some((Precedence::Product, InfixOperator::Divide)),
         Token::Rem(_) => Some((Precedence::Product, InfixOperator::Rem)),
+        Token::Pipeline(_) => Some((Precedence::Pipeline, InfixOperator::Pipeline)),
+        Token::ComposeRight(_) => Some((Precedence::Compose, InfixOperator::ComposeRight)),
+        Token::ComposeLeft(_) => Some((Precedence::Compose, InfixOperator::ComposeLeft)),
+        Token::Dollar(_) => Some((Precedence::Apply, InfixOperator::Apply)),
         _ => None,
     })(input)
 }
@@ -112,6 +153,10 @@ fn infix(
     left: Expression,
     (precedence, operator): (Precedence, InfixOperator),
 ) -> IResult<&str, Infix> {
+    let precedence = match operator {
+        InfixOperator::Apply => Precedence::Lowest,
+        _ => precedence,
+    };
     let (input, right) = pratt(precedence)(input)?;
     Ok((input, Infix::new(operator, left, right)))
 }
@@ -119,24 +164,69 @@ fn infix(
 fn assign(input: &str) -> IResult<&str, Assign> {
     map(
         tuple((
+            many0(preceded(
+                verify_token!(Token::At(_)),
+                pratt(Precedence::Prefix),
+            )),
             map_opt(token, |t| match t {
                 Token::Identifier(x) => Some(Identifier(x.0)),
                 _ => None,
             }),
             verify_token!(Token::Assign(_)),
-            expression,
+            cut(expression),
         )),
-        |(ident, _, expr)| Assign::new(ident, expr),
+        |(decorators, ident, _, expr)| Assign::decorated(decorators, ident, expr),
     )(input)
 }
 
 fn return_(input: &str) -> IResult<&str, Return> {
     map(
-        tuple((verify_token!(Token::Return(_)), expression)),
+        tuple((verify_token!(Token::Return(_)), cut(expression))),
         |(_, expr)| Return(Box::new(expr)),
     )(input)
 }
 
+fn import(input: &str) -> IResult<&str, Import> {
+    preceded(
+        verify_token!(Token::Import(_)),
+        alt((
+            map(import_path, |path| Import {
+                path,
+                binding: ImportBinding::All,
+            }),
+            map(
+                separated_pair(identifier, verify_token!(Token::ImportFrom(_)), import_path),
+                |(name, path)| Import {
+                    path,
+                    binding: ImportBinding::Namespace(name),
+                },
+            ),
+            map(
+                separated_pair(
+                    delimited(
+                        verify_token!(Token::LBrace(_)),
+                        separated_list1(verify_token!(Token::Comma(_)), identifier),
+                        verify_token!(Token::RBrace(_)),
+                    ),
+                    verify_token!(Token::ImportFrom(_)),
+                    import_path,
+                ),
+                |(names, path)| Import {
+                    path,
+                    binding: ImportBinding::Names(names),
+                },
+            ),
+        )),
+    )(input)
+}
+
+fn import_path(input: &str) -> IResult<&str, String> {
+    map_opt(token, |t| match t {
+        Token::Str(x) => Some(x),
+        _ => None,
+    })(input)
+}
+
 fn literal(input: &str) -> IResult<&str, Literal> {
     map_opt(token, |t| match t {
         Token::Str(x) => Some(Literal::Str(x)),
@@ -147,6 +237,22 @@ fn literal(input: &str) -> IResult<&str, Literal> {
     })(input)
 }
 
+fn template(input: &str) -> IResult<&str, Template> {
+    map_opt(token, |t| match t {
+        Token::Template(token::Template(parts)) => parts
+            .into_iter()
+            .map(|part| match part {
+                token::TemplatePart::Str(s) => Some(TemplatePart::Str(s)),
+                token::TemplatePart::Code(code) => all_consuming(expression)(&code)
+                    .ok()
+                    .map(|(_, e)| TemplatePart::Expression(e)),
+            })
+            .collect::<Option<_>>()
+            .map(Template),
+        _ => None,
+    })(input)
+}
+
 fn identifier(input: &str) -> IResult<&str, Identifier> {
     map_opt(token, |t| match t {
         Token::Identifier(x) => Some(Identifier(x.0)),
@@ -168,43 +274,95 @@ fn prefix(input: &str) -> IResult<&str, Prefix> {
 fn parenthesis(input: &str) -> IResult<&str, Expression> {
     delimited(
         verify_token!(Token::LParenthesis(_)),
-        expression,
+        alt((
+            map(infix_operator_before_rparen, |(_, operator)| {
+                Function::section(operator, None, None).into()
+            }),
+            map(
+                pair(
+                    verify(infix_operator, |(_, operator)| {
+                        *operator != InfixOperator::Minus
+                    }),
+                    expression,
+                ),
+                |((_, operator), right)| Function::section(operator, None, Some(right)).into(),
+            ),
+            expression,
+            map(
+                pair(pratt(Precedence::Prefix), infix_operator_before_rparen),
+                |(left, (_, operator))| Function::section(operator, Some(left), None).into(),
+            ),
+        )),
         verify_token!(Token::RParenthesis(_)),
     )(input)
 }
 
+fn infix_operator_before_rparen(input: &str) -> IResult<&str, (Precedence, InfixOperator)> {
+    terminated(infix_operator, peek(verify_token!(Token::RParenthesis(_))))(input)
+}
+
 fn array(input: &str) -> IResult<&str, Array> {
     map(
         delimited(
             verify_token!(Token::LBracket(_)),
-            expressions,
+            elements,
             verify_token!(Token::RBracket(_)),
         ),
-        |x| Array(x),
+        Array,
     )(input)
 }
 
+/// A lone bare name without a trailing comma, `{name}`, is left to parse as a
+/// block; `{name,}` is the hash with one shorthand entry.
 fn hash(input: &str) -> IResult<&str, Hash> {
     map(
         delimited(
             verify_token!(Token::LBrace(_)),
-            separated_list0(
-                verify_token!(Token::Comma(_)),
-                separated_pair(hash_key, verify_token!(Token::Colon(_)), expression),
+            verify(
+                pair(
+                    separated_list0(verify_token!(Token::Comma(_)), hash_entry),
+                    opt(verify_token!(Token::Comma(_))),
+                ),
+                |(entries, trailing)| {
+                    !matches!((entries.as_slice(), trailing), ([(true, _)], None))
+                },
             ),
             verify_token!(Token::RBrace(_)),
         ),
-        |x| Hash(x),
+        |(entries, _)| Hash(entries.into_iter().map(|(_, entry)| entry).collect()),
     )(input)
 }
 
-fn hash_key(input: &str) -> IResult<&str, HashKey> {
-    map_opt(token, |t| match t {
-        Token::Str(x) => Some(HashKey::Str(x)),
-        Token::Int(x) => Some(HashKey::Int(x)),
-        Token::Bool(x) => Some(HashKey::Bool(x)),
-        _ => None,
-    })(input)
+/// A bare identifier key is a string, as in the `{name, age}` shorthand. The
+/// flag marks an entry written as a bare name alone.
+fn hash_entry(input: &str) -> IResult<&str, (bool, (Expression, Expression))> {
+    alt((
+        map(
+            separated_pair(hash_key, verify_token!(Token::Colon(_)), expression),
+            |entry| (false, entry),
+        ),
+        map(identifier, |Identifier(name)| {
+            let key = Literal::Str(name.clone()).into();
+            (true, (key, Identifier(name).into()))
+        }),
+    ))(input)
+}
+
+fn hash_key(input: &str) -> IResult<&str, Expression> {
+    alt((
+        map_opt(token, |t| match t {
+            Token::Str(x) => Some(Literal::Str(x).into()),
+            Token::Int(x) => Some(Literal::Int(x).into()),
+            Token::Bool(x) => Some(Literal::Bool(x).into()),
+            _ => None,
+        }),
+        delimited(
+            verify_token!(Token::LBracket(_)),
+            expression,
+            verify_token!(Token::RBracket(_)),
+        ),
+        map(identifier, |Identifier(name)| Literal::Str(name).into()),
+    ))(input)
 }
 
 fn if_(input: &str) -> IResult<&str, If> {
@@ -224,14 +382,40 @@ fn function(input: &str) -> IResult<&str, Function> {
     map(
         tuple((
             verify_token!(Token::Pipe(_)),
-            separated_list0(verify_token!(Token::Comma(_)), identifier),
+            verify(
+                separated_list0(verify_token!(Token::Comma(_)), parameter),
+                |params: &[Parameter]| params.iter().rev().skip(1).all(|p| !p.rest),
+            ),
             verify_token!(Token::Pipe(_)),
-            expression,
+            cut(expression),
         )),
         |(_, params, _, body)| Function::currying(&params, Box::new(body)),
     )(input)
 }
 
+fn parameter(input: &str) -> IResult<&str, Parameter> {
+    alt((
+        map(
+            preceded(verify_token!(Token::DotDot(_)), identifier),
+            Parameter::rest,
+        ),
+        default_parameter,
+    ))(input)
+}
+
+fn default_parameter(input: &str) -> IResult<&str, Parameter> {
+    map(
+        pair(
+            identifier,
+            opt(preceded(verify_token!(Token::Assign(_)), expression)),
+        ),
+        |(identifier, default)| match default {
+            Some(default) => Parameter::with_default(identifier, default),
+            None => Parameter::new(identifier),
+        },
+    )(input)
+}
+
 fn block(input: &str) -> IResult<&str, Block> {
     map(
         delimited(
@@ -239,6 +423,6 @@ fn block(input: &str) -> IResult<&str, Block> {
             many0(root),
             verify_token!(Token::RBrace(_)),
         ),
-        |x| Block(x),
+        Block,
     )(input)
 }
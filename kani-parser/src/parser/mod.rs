@@ -1,14 +1,31 @@
 mod expression;
 
 use crate::ast::Expression;
+use crate::error::{Error, IResult};
+use crate::lexer::{shebang, skip};
 pub use expression::expression;
 use expression::root;
-use nom::combinator::all_consuming;
+use nom::combinator::opt;
+use nom::error::{ErrorKind, ParseError};
 use nom::multi::many0;
-use nom::IResult;
+use nom::sequence::{pair, preceded};
 
-pub fn program(input: &str) -> IResult<&str, Vec<Expression>> {
-    all_consuming(many0(root))(input)
+/// On failure, the error points at the furthest token the failing statement
+/// reached rather than at where it started.
+pub fn program(input: &str) -> nom::IResult<&str, Vec<Expression>> {
+    statements(input).map_err(|e| e.map(Into::into))
+}
+
+fn statements(input: &str) -> IResult<&str, Vec<Expression>> {
+    let (input, program) = preceded(pair(opt(shebang), skip), many0(root))(input)?;
+    if input.is_empty() {
+        return Ok((input, program));
+    }
+    // `many0` drops the error of the statement that stopped it, so parse that
+    // statement again to get it back.
+    Err(root(input)
+        .err()
+        .unwrap_or_else(|| nom::Err::Error(Error::from_error_kind(input, ErrorKind::Eof))))
 }
 
 #[cfg(test)]
@@ -51,24 +68,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn error_test() {
+        let rest = |code| match program(code) {
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => e.input,
+            o => panic!("unexpected {:?}", o),
+        };
+        assert_eq!(rest("1+"), "");
+        assert_eq!(rest("x = 1; y = (2 +"), "");
+        assert_eq!(rest("f = |a| a *"), "");
+        assert_eq!(rest("1 2 )"), ")");
+    }
+
     #[test]
     fn function_test() {
-        let add = vec![Expression::Assign(Assign {
-            identifier: Identifier("add".to_string()),
-            expression: Box::new(Expression::Function(Function {
-                param: Some(Identifier("a".to_string())),
-                body: Box::new(Expression::Function(Function {
-                    param: Some(Identifier("b".to_string())),
-                    body: Box::new(Expression::Infix(Infix {
-                        operator: InfixOperator::Plus,
-                        left: Box::new(Expression::Identifier(Identifier("a".to_string()))),
-                        right: Box::new(Expression::Identifier(Identifier("b".to_string()))),
+        let add = |curried| {
+            vec![Expression::Assign(Assign {
+                decorators: vec![],
+                identifier: Identifier("add".to_string()),
+                expression: Box::new(Expression::Function(Function {
+                    param: Some(Parameter::new(Identifier("a".to_string()))),
+                    body: Box::new(Expression::Function(Function {
+                        param: Some(Parameter::new(Identifier("b".to_string()))),
+                        body: Box::new(Expression::Infix(Infix {
+                            operator: InfixOperator::Plus,
+                            left: Box::new(Expression::Identifier(Identifier("a".to_string()))),
+                            right: Box::new(Expression::Identifier(Identifier("b".to_string()))),
+                        })),
+                        curried,
                     })),
+                    curried: false,
                 })),
-            })),
-        })];
-        assert_eq!(program("add = |a, b| a + b"), Ok(("", add.clone())));
-        assert_eq!(program("add = |a| |b| a + b"), Ok(("", add)));
+            })]
+        };
+        assert_eq!(program("add = |a, b| a + b"), Ok(("", add(true))));
+        assert_eq!(program("add = |a| |b| a + b"), Ok(("", add(false))));
     }
 
     #[test]
@@ -87,4 +121,455 @@ mod tests {
         assert_eq!(program("add(1, 2)"), Ok(("", add.clone())));
         assert_eq!(program("add(1)(2)"), Ok(("", add)));
     }
+
+    #[test]
+    fn hash_test() {
+        let str = |x: &str| Expression::Literal(Literal::Str(x.to_string()));
+        let ident = |x: &str| Expression::Identifier(Identifier(x.to_string()));
+        assert_eq!(
+            program("{\"a\": 1, [k]: 2}"),
+            Ok((
+                "",
+                vec![Expression::Hash(Hash(vec![
+                    (str("a"), Expression::Literal(Literal::Int(1))),
+                    (ident("k"), Expression::Literal(Literal::Int(2))),
+                ]))]
+            ))
+        );
+        assert_eq!(
+            program("{name, age}"),
+            Ok((
+                "",
+                vec![Expression::Hash(Hash(vec![
+                    (str("name"), ident("name")),
+                    (str("age"), ident("age")),
+                ]))]
+            ))
+        );
+        assert_eq!(
+            program("{name,}"),
+            Ok((
+                "",
+                vec![Expression::Hash(Hash(vec![(str("name"), ident("name"))]))]
+            ))
+        );
+        assert_eq!(
+            program("{name}"),
+            Ok(("", vec![Expression::Block(Block(vec![ident("name")]))]))
+        );
+        assert_eq!(
+            program("{name,}"),
+            Ok((
+                "",
+                vec![Expression::Hash(Hash(vec![(str("name"), ident("name"))]))]
+            ))
+        );
+        assert_eq!(
+            program("{a: 1, name}"),
+            Ok((
+                "",
+                vec![Expression::Hash(Hash(vec![
+                    (str("a"), Expression::Literal(Literal::Int(1))),
+                    (str("name"), ident("name")),
+                ]))]
+            ))
+        );
+        assert_eq!(
+            program("{name;}"),
+            Ok(("", vec![Expression::Block(Block(vec![ident("name")]))]))
+        );
+    }
+
+    #[test]
+    fn field_test() {
+        let field = |name: &str, expression: Expression| {
+            Expression::Postfix(Postfix {
+                operator: PostfixOperator::Field(Field(Identifier(name.to_string()))),
+                expression: Box::new(expression),
+            })
+        };
+        let a = Expression::Identifier(Identifier("a".to_string()));
+        assert_eq!(
+            program("a.b.c"),
+            Ok(("", vec![field("c", field("b", a.clone()))]))
+        );
+        assert_eq!(
+            program("-a.b"),
+            Ok((
+                "",
+                vec![Expression::Prefix(Prefix {
+                    operator: PrefixOperator::Minus,
+                    expression: Box::new(field("b", a)),
+                })]
+            ))
+        );
+    }
+
+    #[test]
+    fn method_test() {
+        let ident = |x: &str| Expression::Identifier(Identifier(x.to_string()));
+        let method = |receiver, name: &str, args: &[Expression]| {
+            let arguments = args
+                .iter()
+                .map(|a| Argument(Some(Box::new(a.clone()))).into())
+                .collect();
+            let method = Method::new(Identifier(name.to_string()), arguments);
+            Expression::Postfix(Postfix::new(method.into(), receiver))
+        };
+        assert_eq!(
+            program("xs.map(f).filter(g)"),
+            Ok((
+                "",
+                vec![method(
+                    method(ident("xs"), "map", &[ident("f")]),
+                    "filter",
+                    &[ident("g")]
+                )]
+            ))
+        );
+        assert_eq!(
+            program("xs.len()"),
+            Ok(("", vec![method(ident("xs"), "len", &[])]))
+        );
+        assert_eq!(
+            program("a.b.f(1)"),
+            Ok((
+                "",
+                vec![method(
+                    program("a.b").unwrap().1[0].clone(),
+                    "f",
+                    &[Literal::Int(1).into()]
+                )]
+            ))
+        );
+    }
+
+    #[test]
+    fn pipeline_test() {
+        let ident = |x: &str| Expression::Identifier(Identifier(x.to_string()));
+        let pipeline = |left, right| {
+            Expression::Infix(Infix {
+                operator: InfixOperator::Pipeline,
+                left: Box::new(left),
+                right: Box::new(right),
+            })
+        };
+        assert_eq!(
+            program("x |> f |> g"),
+            Ok((
+                "",
+                vec![pipeline(pipeline(ident("x"), ident("f")), ident("g"))]
+            ))
+        );
+        assert_eq!(
+            program("a == b |> f"),
+            Ok((
+                "",
+                vec![pipeline(
+                    program("a == b").unwrap().1[0].clone(),
+                    ident("f")
+                )]
+            ))
+        );
+        assert_eq!(
+            program("|x| x |> f"),
+            Ok((
+                "",
+                vec![Expression::Function(Function {
+                    param: Some(Parameter::new(Identifier("x".to_string()))),
+                    body: Box::new(pipeline(ident("x"), ident("f"))),
+                    curried: false,
+                })]
+            ))
+        );
+    }
+
+    #[test]
+    fn compose_test() {
+        let ident = |x: &str| Expression::Identifier(Identifier(x.to_string()));
+        let infix = |operator, left, right| {
+            Expression::Infix(Infix {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            })
+        };
+        assert_eq!(
+            program("x |> f >> g << h"),
+            Ok((
+                "",
+                vec![infix(
+                    InfixOperator::Pipeline,
+                    ident("x"),
+                    infix(
+                        InfixOperator::ComposeLeft,
+                        infix(InfixOperator::ComposeRight, ident("f"), ident("g")),
+                        ident("h"),
+                    ),
+                )]
+            ))
+        );
+        assert_eq!(
+            program("f >> g == h < k << m"),
+            Ok((
+                "",
+                vec![infix(
+                    InfixOperator::Equal,
+                    infix(InfixOperator::ComposeRight, ident("f"), ident("g")),
+                    infix(
+                        InfixOperator::LessThan,
+                        ident("h"),
+                        infix(InfixOperator::ComposeLeft, ident("k"), ident("m")),
+                    ),
+                )]
+            ))
+        );
+    }
+
+    #[test]
+    fn apply_test() {
+        let ident = |x: &str| Expression::Identifier(Identifier(x.to_string()));
+        let apply = |left, right| Expression::Infix(Infix::new(InfixOperator::Apply, left, right));
+        assert_eq!(
+            program("f $ g $ x"),
+            Ok(("", vec![apply(ident("f"), apply(ident("g"), ident("x")))]))
+        );
+        assert_eq!(
+            program("f $ x |> g"),
+            Ok((
+                "",
+                vec![apply(ident("f"), program("x |> g").unwrap().1[0].clone())]
+            ))
+        );
+    }
+
+    #[test]
+    fn decorator_test() {
+        assert_eq!(
+            program("@memo @trace(\"fib\") fib = 1"),
+            Ok((
+                "",
+                vec![Expression::Assign(Assign::decorated(
+                    vec![
+                        Identifier("memo".to_string()).into(),
+                        program("trace(\"fib\")").unwrap().1[0].clone(),
+                    ],
+                    Identifier("fib".to_string()),
+                    Literal::Int(1).into(),
+                ))]
+            ))
+        );
+    }
+
+    #[test]
+    fn section_test() {
+        let ident = |x: &str| Expression::Identifier(Identifier(x.to_string()));
+        let int = |x| Expression::Literal(Literal::Int(x));
+        let function = |param: &str, body| {
+            Expression::Function(Function::new(
+                Some(Parameter::new(Identifier(param.to_string()))),
+                Box::new(body),
+            ))
+        };
+        let infix = |operator, left, right| Expression::Infix(Infix::new(operator, left, right));
+        assert_eq!(
+            program("(+ 1)"),
+            Ok((
+                "",
+                vec![function(
+                    "$left",
+                    infix(InfixOperator::Plus, ident("$left"), int(1))
+                )]
+            ))
+        );
+        assert_eq!(
+            program("(2 *)"),
+            Ok((
+                "",
+                vec![function(
+                    "$right",
+                    infix(InfixOperator::Multiply, int(2), ident("$right"))
+                )]
+            ))
+        );
+        assert_eq!(
+            program("(==)"),
+            Ok((
+                "",
+                vec![function(
+                    "$left",
+                    Function {
+                        curried: true,
+                        ..Function::new(
+                            Some(Parameter::new(Identifier("$right".to_string()))),
+                            Box::new(infix(InfixOperator::Equal, ident("$left"), ident("$right"))),
+                        )
+                    }
+                    .into()
+                )]
+            ))
+        );
+        assert_eq!(program("(- 1)"), program("-1"));
+        assert_eq!(program("(1 + 2)"), program("1 + 2"));
+    }
+
+    #[test]
+    fn default_test() {
+        let ident = |x: &str| Expression::Identifier(Identifier(x.to_string()));
+        assert_eq!(
+            program("|a, b = 10| a + b"),
+            Ok((
+                "",
+                vec![Expression::Function(Function::new(
+                    Some(Parameter::new(Identifier("a".to_string()))),
+                    Box::new(Expression::Function(Function {
+                        curried: true,
+                        ..Function::new(
+                            Some(Parameter::with_default(
+                                Identifier("b".to_string()),
+                                Literal::Int(10).into(),
+                            )),
+                            Box::new(
+                                Infix::new(InfixOperator::Plus, ident("a"), ident("b")).into()
+                            ),
+                        )
+                    })),
+                ))]
+            ))
+        );
+    }
+
+    #[test]
+    fn named_argument_test() {
+        let ident = |x: &str| Expression::Identifier(Identifier(x.to_string()));
+        let named = NamedArgument::new(Identifier("b".to_string()), Literal::Int(2).into());
+        assert_eq!(
+            program("f(1, b: 2)"),
+            Ok((
+                "",
+                vec![Expression::Postfix(Postfix::new(
+                    named.into(),
+                    Postfix::currying(&[Literal::Int(1).into()], ident("f")).into(),
+                ))]
+            ))
+        );
+        assert_eq!(
+            program("f(b: 2)"),
+            Ok((
+                "",
+                vec![Expression::Postfix(Postfix::new(
+                    NamedArgument::new(Identifier("b".to_string()), Literal::Int(2).into()).into(),
+                    ident("f"),
+                ))]
+            ))
+        );
+    }
+
+    #[test]
+    fn rest_test() {
+        let ident = |x: &str| Expression::Identifier(Identifier(x.to_string()));
+        assert_eq!(
+            program("|first, ..rest| rest"),
+            Ok((
+                "",
+                vec![Expression::Function(Function::currying(
+                    &[
+                        Parameter::new(Identifier("first".to_string())),
+                        Parameter::rest(Identifier("rest".to_string())),
+                    ],
+                    Box::new(ident("rest")),
+                ))]
+            ))
+        );
+        assert!(program("|..rest, last| rest").is_err());
+    }
+
+    #[test]
+    fn spread_test() {
+        let ident = |x: &str| Expression::Identifier(Identifier(x.to_string()));
+        let spread = |x| Expression::Spread(Spread(Box::new(ident(x))));
+        assert_eq!(
+            program("[..a, 1, ..b]"),
+            Ok((
+                "",
+                vec![Expression::Array(Array(vec![
+                    spread("a"),
+                    Literal::Int(1).into(),
+                    spread("b"),
+                ]))]
+            ))
+        );
+        assert_eq!(
+            program("f(1, ..xs)"),
+            Ok((
+                "",
+                vec![Postfix::currying(&[Literal::Int(1).into(), spread("xs")], ident("f")).into()]
+            ))
+        );
+    }
+
+    #[test]
+    fn template_test() {
+        assert_eq!(
+            program("\"Hello ${name}, you are ${age + 1}\""),
+            Ok((
+                "",
+                vec![Expression::Template(Template(vec![
+                    TemplatePart::Str("Hello ".to_string()),
+                    TemplatePart::Expression(Identifier("name".to_string()).into()),
+                    TemplatePart::Str(", you are ".to_string()),
+                    TemplatePart::Expression(program("age + 1").unwrap().1[0].clone()),
+                ]))]
+            ))
+        );
+        assert!(program("\"${1 +}\"").is_err());
+    }
+
+    #[test]
+    fn import_test() {
+        let import = |binding| {
+            Ok((
+                "",
+                vec![Expression::Import(Import {
+                    path: "lib/list.kn".to_string(),
+                    binding,
+                })],
+            ))
+        };
+        assert_eq!(
+            program("import \"lib/list.kn\""),
+            import(ImportBinding::All)
+        );
+        assert_eq!(
+            program("import list from \"lib/list.kn\""),
+            import(ImportBinding::Namespace(Identifier("list".to_string())))
+        );
+        assert_eq!(
+            program("import {map, filter} from \"lib/list.kn\""),
+            import(ImportBinding::Names(vec![
+                Identifier("map".to_string()),
+                Identifier("filter".to_string()),
+            ]))
+        );
+        assert!(program("import list").is_err());
+    }
+
+    #[test]
+    fn shebang_test() {
+        assert_eq!(
+            program("#!/usr/bin/env kani\n1"),
+            Ok(("", vec![Expression::Literal(Literal::Int(1))]))
+        );
+        assert_eq!(program("#!/usr/bin/env kani"), Ok(("", vec![])));
+        assert!(program("1\n#!/usr/bin/env kani").is_err());
+    }
+
+    #[test]
+    fn comment_test() {
+        assert_eq!(
+            program("// one\n1 // two\n// three"),
+            Ok(("", vec![Expression::Literal(Literal::Int(1))]))
+        );
+        assert_eq!(program("  // empty\n"), Ok(("", vec![])));
+    }
 }
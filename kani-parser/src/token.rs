@@ -6,6 +6,7 @@ pub enum Token {
     Illegal(Illegal),
     Identifier(Identifier),
     Str(String),
+    Template(Template),
     Int(Int),
     Float(Float),
     Bool(Bool),
@@ -22,11 +23,15 @@ pub enum Token {
     LessThanEqual(LessThanEqual),
     GreaterThan(GreaterThan),
     LessThan(LessThan),
+    Pipeline(Pipeline),
+    ComposeRight(ComposeRight),
+    ComposeLeft(ComposeLeft),
     At(At),
     Dollar(Dollar),
     Comma(Comma),
     SemiColon(SemiColon),
     Colon(Colon),
+    DotDot(DotDot),
     Dot(Dot),
     Pipe(Pipe),
     LParenthesis(LParenthesis),
@@ -39,6 +44,8 @@ pub enum Token {
     If(If),
     Then(Then),
     Else(Else),
+    Import(Import),
+    ImportFrom(ImportFrom),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -72,6 +79,12 @@ pub struct GreaterThan;
 #[derive(Debug, Clone, PartialEq)]
 pub struct LessThan;
 #[derive(Debug, Clone, PartialEq)]
+pub struct Pipeline;
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComposeRight;
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComposeLeft;
+#[derive(Debug, Clone, PartialEq)]
 pub struct At;
 #[derive(Debug, Clone, PartialEq)]
 pub struct Dollar;
@@ -82,10 +95,19 @@ pub struct SemiColon;
 #[derive(Debug, Clone, PartialEq)]
 pub struct Colon;
 #[derive(Debug, Clone, PartialEq)]
+pub struct DotDot;
+#[derive(Debug, Clone, PartialEq)]
 pub struct Dot;
 #[derive(Debug, Clone, PartialEq)]
 pub struct Pipe;
 #[derive(Debug, Clone, PartialEq)]
+pub struct Template(pub Vec<TemplatePart>);
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePart {
+    Str(String),
+    Code(String),
+}
+#[derive(Debug, Clone, PartialEq)]
 pub struct LParenthesis;
 #[derive(Debug, Clone, PartialEq)]
 pub struct RParenthesis;
@@ -105,10 +127,15 @@ pub struct If;
 pub struct Then;
 #[derive(Debug, Clone, PartialEq)]
 pub struct Else;
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import;
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportFrom;
 
 impl_from_enum!(Token::Illegal, Illegal);
 impl_from_enum!(Token::Identifier, Identifier);
 impl_from_enum!(Token::Str, String);
+impl_from_enum!(Token::Template, Template);
 impl_from_enum!(Token::Int, Int);
 impl_from_enum!(Token::Float, Float);
 impl_from_enum!(Token::Bool, Bool);
@@ -125,11 +152,15 @@ impl_from_enum!(Token::GreaterThanEqual, GreaterThanEqual);
 impl_from_enum!(Token::LessThanEqual, LessThanEqual);
 impl_from_enum!(Token::GreaterThan, GreaterThan);
 impl_from_enum!(Token::LessThan, LessThan);
+impl_from_enum!(Token::Pipeline, Pipeline);
+impl_from_enum!(Token::ComposeRight, ComposeRight);
+impl_from_enum!(Token::ComposeLeft, ComposeLeft);
 impl_from_enum!(Token::At, At);
 impl_from_enum!(Token::Dollar, Dollar);
 impl_from_enum!(Token::Comma, Comma);
 impl_from_enum!(Token::SemiColon, SemiColon);
 impl_from_enum!(Token::Colon, Colon);
+impl_from_enum!(Token::DotDot, DotDot);
 impl_from_enum!(Token::Dot, Dot);
 impl_from_enum!(Token::Pipe, Pipe);
 impl_from_enum!(Token::LParenthesis, LParenthesis);
@@ -142,5 +173,7 @@ impl_from_enum!(Token::Return, Return);
 impl_from_enum!(Token::If, If);
 impl_from_enum!(Token::Then, Then);
 impl_from_enum!(Token::Else, Else);
+impl_from_enum!(Token::Import, Import);
+impl_from_enum!(Token::ImportFrom, ImportFrom);
 
 impl_deref!(Identifier, String);